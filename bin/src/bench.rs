@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use mm_maze_solver::maze::{Maze, MazeConfig};
+use mm_maze_solver::solver::{StepMap, StepMapMode};
+
+use crate::reader;
+
+const ITERATIONS: u32 = 100;
+
+// Compares the queue based step map with the relaxation one on every maze.
+pub fn run(files: &[PathBuf]) {
+    let mut relaxation_map: StepMap = StepMap::new();
+    let mut bfs_map: StepMap = StepMap::new();
+    let mut relaxation_total = Duration::ZERO;
+    let mut bfs_total = Duration::ZERO;
+
+    println!("{:>12} {:>12}  maze", "relaxation", "bfs");
+    for file in files {
+        let (maze, config): (Maze, MazeConfig) = match reader::read(file) {
+            Ok(read) => read,
+            Err(e) => {
+                println!("Skipping {}: {}", file.display(), e);
+                continue;
+            }
+        };
+
+        let mut relaxation = Duration::ZERO;
+        let mut bfs = Duration::ZERO;
+        for mode in [
+            StepMapMode::UnexploredAsAbsent,
+            StepMapMode::UnexploredAsPresent,
+        ] {
+            let start = Instant::now();
+            for _ in 0..ITERATIONS {
+                relaxation_map.calc_step_map_by_relaxation(&maze, mode, &config.goal);
+            }
+            relaxation += start.elapsed();
+
+            let start = Instant::now();
+            for _ in 0..ITERATIONS {
+                bfs_map.calc_step_map(&maze, mode, &config.goal);
+            }
+            bfs += start.elapsed();
+
+            assert!(
+                relaxation_map.grid == bfs_map.grid,
+                "Step maps differ for {}",
+                file.display()
+            );
+        }
+
+        println!(
+            "{:>10}us {:>10}us  {}",
+            relaxation.as_micros() / (2 * ITERATIONS) as u128,
+            bfs.as_micros() / (2 * ITERATIONS) as u128,
+            file.display()
+        );
+        relaxation_total += relaxation;
+        bfs_total += bfs;
+    }
+
+    println!(
+        "total: relaxation {:?}, bfs {:?} ({} mazes x {} iterations x 2 modes)",
+        relaxation_total,
+        bfs_total,
+        files.len(),
+        ITERATIONS
+    );
+}
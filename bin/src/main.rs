@@ -1,85 +1,121 @@
+mod bench;
 mod reader;
+mod strategies;
 use glob::glob;
 use mm_maze_solver::maze;
 use mm_maze_solver::solver;
+use std::path::Path;
 
-use maze::{Maze, MAZE_SIZE, TOZAINANBOKU};
+use maze::{Maze, MazeConfig};
+use mm_maze_solver::sim::{Simulator, Step};
 use solver::StepMap;
 
-use crate::solver::decide_direction;
-
 fn main() {
-    let mut actual_maze = Maze::new();
-    let mut goal_x: usize = 0;
-    let mut goal_y: usize = 0;
-
-    let files = glob("assets/*.txt")
-        .unwrap()
+    let files = ["assets/*.txt", "assets/*.maz"]
+        .iter()
+        .flat_map(|pattern| glob(pattern).unwrap())
         .map(|e| e.unwrap())
         .collect::<Vec<_>>();
-    println!("{:?}", files);
-    for file in files {
-        reader::read(
-            &mut actual_maze,
-            &mut goal_x,
-            &mut goal_y,
-            String::from(file.to_str().unwrap()),
-        );
 
-        for line in actual_maze.lines_iter(goal_x, goal_y) {
-            println!("{}", line);
-        }
+    // `cargo run --release -- bench` compares the step map implementations.
+    if std::env::args().nth(1).as_deref() == Some("bench") {
+        bench::run(&files);
+        return;
+    }
+    // `cargo run -- strategies` compares the search strategies.
+    if std::env::args().nth(1).as_deref() == Some("strategies") {
+        strategies::run(&files);
+        return;
+    }
 
-        println!("{}", file.to_str().unwrap());
-        if !simulate(&actual_maze, goal_x, goal_y) {
+    println!("{:?}", files);
+    for file in files {
+        let reached = match reader::size(&file) {
+            Ok((16, 16)) => run::<16, 16>(&file),
+            Ok((32, 32)) => run::<32, 32>(&file),
+            Ok((width, height)) => {
+                println!(
+                    "Skipping {}: {}x{} mazes are not supported",
+                    file.display(),
+                    width,
+                    height
+                );
+                continue;
+            }
+            Err(e) => {
+                println!("Skipping {}: {}", file.display(), e);
+                continue;
+            }
+        };
+        if !reached {
             println!("Cannot reach the goal!");
             break;
         }
     }
 }
 
-fn display(stepmap: &StepMap) {
-    for i in 0..MAZE_SIZE {
-        for j in 0..MAZE_SIZE {
-            print!("{:^4X} ", stepmap.get(i, j));
+// Returns false if the goal cannot be reached. Unreadable files are skipped.
+fn run<const W: usize, const H: usize>(file: &Path) -> bool {
+    let (actual_maze, config): (Maze<W, H>, MazeConfig) = match reader::read(file) {
+        Ok(read) => read,
+        Err(e) => {
+            println!("Skipping {}: {}", file.display(), e);
+            return true;
         }
-        println!("");
+    };
+
+    for line in actual_maze.lines_iter(&config) {
+        println!("{}", line);
     }
+
+    println!("{}", file.display());
+    simulate(actual_maze, &config)
 }
 
-fn update_wall(actual_maze: &Maze, row: usize, col: usize, local_maze: &mut Maze) {
-    for d in TOZAINANBOKU {
-        local_maze.set_wall(row, col, d, actual_maze.get(row, col).get(d));
+fn display<const W: usize, const H: usize>(stepmap: &StepMap<W, H>) {
+    for i in 0..H {
+        for j in 0..W {
+            print!("{:^4X} ", stepmap.get(i, j));
+        }
+        println!();
     }
 }
 
-fn simulate(actual_maze: &Maze, goal_x: usize, goal_y: usize) -> bool {
-    let mut x: usize = 0;
-    let mut y: usize = 0;
-    let mut stepmap = StepMap::new();
-    let mut local_maze = Maze::new();
+fn simulate<const W: usize, const H: usize>(actual_maze: Maze<W, H>, config: &MazeConfig) -> bool {
+    let mut simulator = Simulator::new(actual_maze, *config);
+    if !walk(&mut simulator) {
+        println!("Cannot reach the goal!");
+        return false;
+    }
+    display(simulator.step_map());
+    println!("{:?}", simulator.result());
+    println!("Shortest route proven: {}", simulator.run_until_proven());
 
-    while x != goal_x || y != goal_y {
-        match decide_direction(&local_maze, goal_x, goal_y, y, x, &mut stepmap) {
-            Some(dir_to_go) => {
-                let update_x: isize;
-                let update_y: isize;
-                (update_x, update_y) = maze::nsew_to_index(dir_to_go);
-                x = ((x as isize) + update_x) as usize;
-                y = ((y as isize) + update_y) as usize;
+    simulator.return_to_start();
+    if !walk(&mut simulator) {
+        println!("Cannot return to the start!");
+        return false;
+    }
+    display(simulator.step_map());
+    println!("{:?}", simulator.result());
+    println!("{:?}", simulator.path_coverage());
 
-                update_wall(&actual_maze, y, x, &mut local_maze);
+    true
+}
 
-                println!("d: {:?}, x: {}, y: {}", dir_to_go, x, y);
-            }
-            None => {
-                println!("Cannot reach the goal!");
-                return false;
+// Steps until the mouse stands in the cells it heads for. False if it cannot reach them.
+fn walk<const W: usize, const H: usize>(simulator: &mut Simulator<W, H>) -> bool {
+    loop {
+        match simulator.step() {
+            Step::Moved(dir_to_go) => {
+                let position = simulator.position();
+                println!(
+                    "d: {:?}, x: {}, y: {}",
+                    dir_to_go, position.col, position.row
+                );
             }
+            Step::AtGoal => return true,
+            Step::Stopped => return false,
         }
     }
-
-    display(&stepmap);
-
-    true
 }
@@ -1,138 +1,79 @@
-use std::fs::File;
-use std::io::prelude::*;
+use std::fmt;
+use std::fs;
 use std::path::Path;
 
-use super::maze::{Direction, Maze, Wall, MAZE_SIZE};
+use mm_maze_solver::maze::maz::{self, LengthError};
+use mm_maze_solver::maze::text::{self, ParseError};
+use mm_maze_solver::maze::{Maze, MazeConfig};
 
-enum State {
-    Pillar,
-    Cell,
-    HorizontalWall,
-    VerticalWall,
+#[derive(Debug)]
+pub enum ReadError {
+    // The file could not be opened or read
+    Io(std::io::Error),
+    Parse(ParseError),
+    Maz(LengthError),
 }
 
-struct ReaderStateMachine {
-    pub state: State,
-    pub row: usize,
-    pub col: usize,
-}
-
-impl ReaderStateMachine {
-    pub const fn new() -> Self {
-        ReaderStateMachine {
-            state: State::Pillar,
-            row: 0,
-            col: 0,
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "{}", e),
+            ReadError::Parse(e) => write!(f, "{}", e),
+            ReadError::Maz(e) => write!(f, "{}", e),
         }
     }
 }
 
-pub fn read(maze: &mut Maze, goal_x: &mut usize, goal_y: &mut usize, filename: String) {
-    let path = Path::new(&filename);
-    let mut file = match File::open(&path) {
-        Err(_) => panic!("couldn't open {}", path.display()),
-        Ok(file) => file,
-    };
-    let mut s = String::new();
-    match file.read_to_string(&mut s) {
-        Err(_) => panic!("couldn't read {}", path.display()),
-        Ok(_) => (),
+impl std::error::Error for ReadError {}
+
+impl From<std::io::Error> for ReadError {
+    fn from(e: std::io::Error) -> Self {
+        ReadError::Io(e)
     }
+}
 
-    let mut machine = ReaderStateMachine::new();
+impl From<ParseError> for ReadError {
+    fn from(e: ParseError) -> Self {
+        ReadError::Parse(e)
+    }
+}
 
-    *maze = Maze::new();
+impl From<LengthError> for ReadError {
+    fn from(e: LengthError) -> Self {
+        ReadError::Maz(e)
+    }
+}
 
-    for c in s.chars() {
-        match machine.state {
-            State::Pillar => match c {
-                '+' => {
-                    machine.state = State::HorizontalWall;
-                }
-                _ => panic!("Expected +, found '{}'", c),
-            },
-            State::HorizontalWall => {
-                // '-' or ' '
-                match c {
-                    '-' => {
-                        if machine.row == MAZE_SIZE {
-                            maze.set_wall(
-                                MAZE_SIZE - 1,
-                                machine.col,
-                                Direction::South,
-                                Wall::Present,
-                            );
-                        } else {
-                            maze.set_wall(
-                                machine.row,
-                                machine.col,
-                                Direction::North,
-                                Wall::Present,
-                            );
-                        }
-                        machine.col += 1;
-                        machine.state = State::Pillar;
-                        if machine.col > MAZE_SIZE + 1 {
-                            panic!("Too many columns in maze");
-                        }
-                    }
-                    ' ' => {
-                        machine.col += 1;
-                        machine.state = State::Pillar;
-                        if machine.col > MAZE_SIZE + 1 {
-                            panic!("Too many columns in maze");
-                        }
-                    }
-                    '\n' => {
-                        machine.col = 0;
-                        machine.state = State::VerticalWall;
-                    }
-                    _ => panic!("Expected - or Space, found '{}'", c),
-                }
-            }
-            State::VerticalWall => {
-                // '|' or ' '
-                match c {
-                    '|' => {
-                        if machine.col == MAZE_SIZE {
-                            maze.set_wall(
-                                machine.row,
-                                MAZE_SIZE - 1,
-                                Direction::East,
-                                Wall::Present,
-                            );
-                        } else {
-                            maze.set_wall(machine.row, machine.col, Direction::West, Wall::Present);
-                        }
-                        machine.col += 1;
-                        machine.state = State::Cell;
-                    }
-                    ' ' => {
-                        machine.col += 1;
-                        machine.state = State::Cell;
-                    }
-                    _ => panic!("Expected |, found '{}'", c),
-                }
-            }
-            State::Cell => match c {
-                ' ' => {
-                    machine.state = State::VerticalWall;
-                }
-                'G' => {
-                    machine.state = State::VerticalWall;
-                    *goal_x = machine.col - 1;
-                    *goal_y = machine.row;
-                }
-                'S' => {
-                    machine.state = State::VerticalWall;
-                }
-                '\n' => {
-                    machine.row += 1;
-                    machine.col = 0;
-                    machine.state = State::Pillar;
-                }
-                _ => panic!("Expected ' ', found '{}'", c),
-            },
+// Number of columns and rows of the maze. .maz files are taken to be square.
+pub fn size(path: &Path) -> Result<(usize, usize), ReadError> {
+    if is_maz(path) {
+        let found = fs::metadata(path)?.len() as usize;
+        let side = (1..).find(|side| side * side >= found).unwrap();
+        if side * side != found {
+            return Err(ReadError::Maz(LengthError {
+                expected: side * side,
+                found,
+            }));
         }
+        return Ok((side, side));
+    }
+    let layout = text::detect(&fs::read_to_string(path)?)?;
+    Ok((layout.width, layout.height))
+}
+
+fn is_maz(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("maz"))
+}
+
+// .maz files carry no start and goal, so they get the classic ones.
+pub fn read<const W: usize, const H: usize>(
+    path: &Path,
+) -> Result<(Maze<W, H>, MazeConfig), ReadError> {
+    if is_maz(path) {
+        let bytes = fs::read(path)?;
+        return Ok((maz::parse(&bytes)?, MazeConfig::classic::<W, H>()));
     }
+    let s = fs::read_to_string(path)?;
+    Ok(text::parse(&s)?)
 }
@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use mm_maze_solver::maze::{Maze, MazeConfig};
+use mm_maze_solver::rules::Hand;
+use mm_maze_solver::search::{
+    Adachi, FullExploration, SearchStrategy, ShortestCandidates, WallFollower,
+};
+use mm_maze_solver::sim::{SimResult, Simulator};
+
+use crate::reader;
+
+// Compares the search strategies on every maze by the steps they take until they stop.
+pub fn run(files: &[PathBuf]) {
+    println!(
+        "{:>8} {:>8} {:>8} {:>8} {:>8}  maze",
+        "adachi", "left", "right", "full", "shortest"
+    );
+    for file in files {
+        let (actual, config): (Maze, MazeConfig) = match reader::read(file) {
+            Ok(read) => read,
+            Err(e) => {
+                println!("Skipping {}: {}", file.display(), e);
+                continue;
+            }
+        };
+        let results = [
+            simulate(&actual, &config, Adachi::new()),
+            simulate(&actual, &config, WallFollower::new(Hand::Left)),
+            simulate(&actual, &config, WallFollower::new(Hand::Right)),
+            simulate(&actual, &config, FullExploration::new()),
+            simulate(&actual, &config, ShortestCandidates::new(config.start)),
+        ];
+        for result in results {
+            // Steps of the strategies which stop elsewhere than in the goal are marked with *.
+            let mark = if result.reached_goal { ' ' } else { '*' };
+            print!("{:>8}{}", result.steps, mark);
+        }
+        println!(" {}", file.display());
+    }
+}
+
+fn simulate(
+    actual: &Maze,
+    config: &MazeConfig,
+    strategy: impl SearchStrategy<16, 16>,
+) -> SimResult {
+    Simulator::with_strategy(actual.clone(), *config, strategy).run_to_goal()
+}
@@ -0,0 +1,177 @@
+use super::heap::Heap;
+use super::maze::{
+    fblr_to_nsew, nsew_to_fblr, opposite, Direction, DirectionOfTravel, Goal, MazeInfo,
+    MazeStorage, Position, Wall, MAZE_SIZE, NESW,
+};
+use super::route::Route;
+
+/*
+    Turn-aware planning for the fastest run.
+
+    A state is a cell and the heading of the mouse in it. The cost of a state is the minimum
+    cost to reach the goal from it, where moving to a neighbor costs one straight cell plus
+    the turn needed to face it. Indices of the headings follow NESW.
+
+    Only walls known to be absent are passed (StepMapMode::UnexploredAsPresent).
+*/
+
+pub type CostMap<const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> = MazeInfo<[u32; 4], W, H>;
+
+pub const UNREACHABLE: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostParams {
+    // Cost of moving one cell ahead. It must not be 0.
+    pub straight: u32,
+    // Additional cost of turning right or left in a cell
+    pub turn90: u32,
+    // Additional cost of turning back in a cell
+    pub uturn: u32,
+}
+
+impl Default for CostParams {
+    fn default() -> Self {
+        CostParams {
+            straight: 10,
+            turn90: 20,
+            uturn: 60,
+        }
+    }
+}
+
+impl CostParams {
+    pub fn turn(&self, heading: Direction, direction: Direction) -> u32 {
+        match nsew_to_fblr(heading, direction) {
+            DirectionOfTravel::Forward => 0,
+            DirectionOfTravel::Right | DirectionOfTravel::Left => self.turn90,
+            DirectionOfTravel::Backward => self.uturn,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize> Default for MazeInfo<[u32; 4], W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> MazeInfo<[u32; 4], W, H> {
+    // CostMap
+    pub fn new() -> Self {
+        CostMap {
+            grid: [[[UNREACHABLE; 4]; W]; H],
+        }
+    }
+
+    // Dijkstra's algorithm from the goal cells, following the moves backwards.
+    pub fn calc_cost_map(
+        &mut self,
+        maze: &impl MazeStorage<W, H>,
+        params: &CostParams,
+        goal: &Goal,
+    ) {
+        let mut items = [[[0u16; 4]; W]; H];
+        let mut positions = [[[0u16; 4]; W]; H];
+        let mut heap = Heap::new(
+            items.as_flattened_mut().as_flattened_mut(),
+            positions.as_flattened_mut().as_flattened_mut(),
+        );
+
+        self.grid = [[[UNREACHABLE; 4]; W]; H];
+        for position in goal.cells() {
+            for heading in 0..4 {
+                self.grid[position.row][position.col][heading] = 0;
+                heap.push(
+                    state_index::<W>(position.row, position.col, heading),
+                    self.grid.as_flattened().as_flattened(),
+                );
+            }
+        }
+
+        while let Some(state) = heap.pop(self.grid.as_flattened().as_flattened()) {
+            // The mouse arrives at (row, col) moving in `direction`
+            let (row, col, heading) = (state / 4 / W, state / 4 % W, state % 4);
+            let direction = NESW[heading];
+            let cost = self.grid[row][col][heading] + params.straight;
+            let from = match self.get_neighbor(row, col, opposite(direction)) {
+                Some(_) => Position::new(row, col).neighbor_position(opposite(direction)),
+                None => continue,
+            };
+            if maze.get_cell(from.row, from.col).get(direction) != Wall::Absent {
+                continue;
+            }
+            for (from_heading, &from_direction) in NESW.iter().enumerate() {
+                let from_cost = cost + params.turn(from_direction, direction);
+                if from_cost < self.grid[from.row][from.col][from_heading] {
+                    self.grid[from.row][from.col][from_heading] = from_cost;
+                    heap.push(
+                        state_index::<W>(from.row, from.col, from_heading),
+                        self.grid.as_flattened().as_flattened(),
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn cost(&self, row: usize, col: usize, heading: Direction) -> u32 {
+        self.grid[row][col][heading as usize]
+    }
+
+    // Direction of the next move on the cheapest route from (row, col) when facing `heading`.
+    // Straight ahead is preferred, then right, left and back.
+    pub fn decide_direction(
+        &self,
+        maze: &impl MazeStorage<W, H>,
+        params: &CostParams,
+        row: usize,
+        col: usize,
+        heading: Direction,
+    ) -> Option<Direction> {
+        let mut min_cost = UNREACHABLE;
+        let mut direction_to_go = None;
+
+        for travel in [
+            DirectionOfTravel::Forward,
+            DirectionOfTravel::Right,
+            DirectionOfTravel::Left,
+            DirectionOfTravel::Backward,
+        ] {
+            let d = fblr_to_nsew(heading, travel);
+            if maze.get_cell(row, col).get(d) != Wall::Absent
+                || self.get_neighbor(row, col, d).is_none()
+            {
+                continue;
+            }
+            let next = Position::new(row, col).neighbor_position(d);
+            let next_cost = self.cost(next.row, next.col, d);
+            if next_cost == UNREACHABLE {
+                continue;
+            }
+            let cost = next_cost + params.straight + params.turn(heading, d);
+            if cost < min_cost {
+                min_cost = cost;
+                direction_to_go = Some(d);
+            }
+        }
+        direction_to_go
+    }
+
+    // Minimum cost route from `start` facing `heading` to `goal`.
+    // The cost map must have been calculated for `goal`. None if the goal cannot be reached.
+    pub fn route(
+        &self,
+        maze: &impl MazeStorage<W, H>,
+        params: &CostParams,
+        goal: &Goal,
+        start: Position,
+        heading: Direction,
+    ) -> Option<Route<W, H>> {
+        Route::follow(start, heading, goal, |position, heading| {
+            self.decide_direction(maze, params, position.row, position.col, heading)
+        })
+    }
+}
+
+fn state_index<const W: usize>(row: usize, col: usize, heading: usize) -> usize {
+    (row * W + col) * 4 + heading
+}
@@ -0,0 +1,522 @@
+use super::heap::Heap;
+use super::maze::{
+    nsew_to_fblr, opposite, Direction, DirectionOfTravel, Goal, MazeInfo, MazeStorage, Position,
+    Wall, MAZE_SIZE,
+};
+use super::route::Route;
+
+/*
+    Path planning with diagonal runs.
+
+    The nodes are the middles of the walls between cells. The node of the north wall of
+    (row, col) has the side North, the node of its west wall has the side West.
+
+       +--N--+
+       |     |
+       W     |
+       |     |
+       +-----+
+
+    The mouse goes from a node through the cell ahead to another wall of that cell.
+    Leaving by the opposite wall is an orthogonal move over one cell, leaving by a wall on a side
+    is a diagonal move over half a diagonal. A zig-zag of cells is a run of diagonal moves
+    in the same heading.
+
+    A state is a node and the heading of the move that reached it. The cost of a state is the
+    minimum cost to enter a goal cell from it. Only walls known to be absent are passed
+    (StepMapMode::UnexploredAsPresent).
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heading {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+// Same order as the discriminants of Heading, clockwise in steps of 45 degrees
+pub const HEADINGS: [Heading; 8] = [
+    Heading::North,
+    Heading::NorthEast,
+    Heading::East,
+    Heading::SouthEast,
+    Heading::South,
+    Heading::SouthWest,
+    Heading::West,
+    Heading::NorthWest,
+];
+
+impl From<Direction> for Heading {
+    fn from(direction: Direction) -> Self {
+        HEADINGS[direction as usize * 2]
+    }
+}
+
+impl Heading {
+    pub fn is_diagonal(self) -> bool {
+        (self as usize) % 2 == 1
+    }
+
+    // Rotated clockwise by `steps` times 45 degrees. Negative steps rotate counterclockwise.
+    pub fn rotate(self, steps: isize) -> Heading {
+        HEADINGS[(self as isize + steps).rem_euclid(8) as usize]
+    }
+
+    // Signed angle from self to `to` in steps of 45 degrees, in -3..=4. Clockwise is positive.
+    pub fn angle_to(self, to: Heading) -> isize {
+        let angle = (to as isize - self as isize).rem_euclid(8);
+        if angle > 4 {
+            angle - 8
+        } else {
+            angle
+        }
+    }
+
+    // None for diagonal headings
+    pub fn to_direction(self) -> Option<Direction> {
+        match self {
+            Heading::North => Some(Direction::North),
+            Heading::East => Some(Direction::East),
+            Heading::South => Some(Direction::South),
+            Heading::West => Some(Direction::West),
+            _ => None,
+        }
+    }
+
+    // The north or south part of the heading
+    pub fn vertical(self) -> Option<Direction> {
+        match self {
+            Heading::North | Heading::NorthEast | Heading::NorthWest => Some(Direction::North),
+            Heading::South | Heading::SouthEast | Heading::SouthWest => Some(Direction::South),
+            Heading::East | Heading::West => None,
+        }
+    }
+
+    // The east or west part of the heading
+    pub fn horizontal(self) -> Option<Direction> {
+        match self {
+            Heading::East | Heading::NorthEast | Heading::SouthEast => Some(Direction::East),
+            Heading::West | Heading::NorthWest | Heading::SouthWest => Some(Direction::West),
+            Heading::North | Heading::South => None,
+        }
+    }
+}
+
+// Wall through which a move in `heading` leaves a cell entered through the wall on `entry`.
+// None if the move cannot start from that wall.
+pub fn exit_side(entry: Direction, heading: Heading) -> Option<Direction> {
+    let travel = opposite(entry);
+    match (heading.vertical(), heading.horizontal()) {
+        (Some(v), Some(h)) if v == travel => Some(h),
+        (Some(v), Some(h)) if h == travel => Some(v),
+        (Some(v), None) if v == travel => Some(v),
+        (None, Some(h)) if h == travel => Some(h),
+        _ => None,
+    }
+}
+
+// Heading between two perpendicular directions, e.g. NorthEast for North and East.
+// A direction with itself gives the direction.
+pub fn diagonal_between(a: Direction, b: Direction) -> Heading {
+    let (a, b) = (Heading::from(a), Heading::from(b));
+    match a.angle_to(b) {
+        2 => a.rotate(1),
+        -2 => a.rotate(-1),
+        _ => a,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WallNode {
+    pub row: usize,
+    pub col: usize,
+    // North or West
+    pub side: Direction,
+}
+
+impl WallNode {
+    // Node of the wall of (row, col) in `direction`. None for the walls around the maze.
+    pub fn of<const W: usize, const H: usize>(
+        row: usize,
+        col: usize,
+        direction: Direction,
+    ) -> Option<WallNode> {
+        let (row, col, side) = match direction {
+            Direction::North if row > 0 => (row, col, Direction::North),
+            Direction::South if row + 1 < H => (row + 1, col, Direction::North),
+            Direction::West if col > 0 => (row, col, Direction::West),
+            Direction::East if col + 1 < W => (row, col + 1, Direction::West),
+            _ => return None,
+        };
+        Some(WallNode { row, col, side })
+    }
+
+    // Cell entered when leaving the node in `heading`, and the wall of that cell the node is on.
+    pub fn cell_ahead(&self, heading: Heading) -> Option<(Position, Direction)> {
+        match (self.side, heading.vertical(), heading.horizontal()) {
+            (Direction::North, Some(Direction::North), _) => {
+                Some((Position::new(self.row - 1, self.col), Direction::South))
+            }
+            (Direction::North, Some(Direction::South), _) => {
+                Some((Position::new(self.row, self.col), Direction::North))
+            }
+            (Direction::West, _, Some(Direction::East)) => {
+                Some((Position::new(self.row, self.col), Direction::West))
+            }
+            (Direction::West, _, Some(Direction::West)) => {
+                Some((Position::new(self.row, self.col - 1), Direction::East))
+            }
+            _ => None,
+        }
+    }
+
+    // Cell left when arriving at the node in `heading`, and the wall of that cell the node is on.
+    pub fn cell_behind(&self, heading: Heading) -> Option<(Position, Direction)> {
+        self.cell_ahead(heading.rotate(4))
+    }
+
+    fn wall<const W: usize, const H: usize>(&self, maze: &impl MazeStorage<W, H>) -> Wall {
+        maze.get_cell(self.row, self.col).get(self.side)
+    }
+
+    fn index(&self) -> usize {
+        match self.side {
+            Direction::North => 0,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiagonalCostParams {
+    // Cost of an orthogonal move over one cell. It must not be 0.
+    pub straight: u32,
+    // Cost of a diagonal move over half a diagonal of a cell. It must not be 0.
+    pub diagonal: u32,
+    // Additional cost of a change of heading by 45 degrees
+    pub turn45: u32,
+    // Additional cost of a change of heading by 90 degrees
+    pub turn90: u32,
+}
+
+impl Default for DiagonalCostParams {
+    fn default() -> Self {
+        DiagonalCostParams {
+            straight: 10,
+            diagonal: 7,
+            turn45: 10,
+            turn90: 20,
+        }
+    }
+}
+
+impl DiagonalCostParams {
+    pub fn moving(&self, heading: Heading) -> u32 {
+        if heading.is_diagonal() {
+            self.diagonal
+        } else {
+            self.straight
+        }
+    }
+
+    // None for changes that cannot be done between two moves
+    pub fn turn(&self, from: Heading, to: Heading) -> Option<u32> {
+        match from.angle_to(to).abs() {
+            0 => Some(0),
+            1 => Some(self.turn45),
+            2 => Some(self.turn90),
+            _ => None,
+        }
+    }
+}
+
+// The path starts at the center of the start cell. The first move goes to the wall the mouse
+// leaves the start cell through, every other move goes from node to node.
+// The path ends at a node of a goal cell, entering that cell.
+pub struct DiagonalPath<const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> {
+    start: Position,
+    heading: Direction,
+    moves: [[[Heading; 2]; W]; H],
+    len: usize,
+}
+
+impl<const W: usize, const H: usize> DiagonalPath<W, H> {
+    pub fn new(start: Position, heading: Direction) -> Self {
+        DiagonalPath {
+            start,
+            heading,
+            moves: [[[Heading::North; 2]; W]; H],
+            len: 0,
+        }
+    }
+
+    // Path along a route over cells. A turn in a cell becomes a diagonal move.
+    // None if the route turns back in a cell after the start.
+    pub fn from_route(route: &Route<W, H>) -> Option<Self> {
+        let mut path = DiagonalPath::new(route.start(), route.heading());
+        let moves = route.moves();
+        if let Some(&first) = moves.first() {
+            path.push(Heading::from(first));
+        }
+        for pair in moves.windows(2) {
+            let heading = match nsew_to_fblr(pair[0], pair[1]) {
+                DirectionOfTravel::Forward => Heading::from(pair[1]),
+                DirectionOfTravel::Right | DirectionOfTravel::Left => {
+                    diagonal_between(pair[0], pair[1])
+                }
+                DirectionOfTravel::Backward => return None,
+            };
+            path.push(heading);
+        }
+        Some(path)
+    }
+
+    // Returns false if the path is full.
+    pub fn push(&mut self, heading: Heading) -> bool {
+        if self.len == W * H * 2 {
+            return false;
+        }
+        self.moves.as_flattened_mut().as_flattened_mut()[self.len] = heading;
+        self.len += 1;
+        true
+    }
+
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    // Heading of the mouse at the start cell, before the first move
+    pub fn heading(&self) -> Direction {
+        self.heading
+    }
+
+    pub fn moves(&self) -> &[Heading] {
+        &self.moves.as_flattened().as_flattened()[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Nodes passed by the path and the headings that reached them
+    pub fn nodes(&self) -> impl Iterator<Item = (WallNode, Heading)> + '_ {
+        let mut node: Option<WallNode> = None;
+        self.moves().iter().map_while(move |&heading| {
+            let next = match node {
+                None => {
+                    WallNode::of::<W, H>(self.start.row, self.start.col, heading.to_direction()?)
+                }
+                Some(n) => {
+                    let (cell, entry) = n.cell_ahead(heading)?;
+                    WallNode::of::<W, H>(cell.row, cell.col, exit_side(entry, heading)?)
+                }
+            }?;
+            node = Some(next);
+            Some((next, heading))
+        })
+    }
+}
+
+pub type DiagonalCostMap<const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> =
+    MazeInfo<[[u32; 8]; 2], W, H>;
+
+pub const UNREACHABLE: u32 = u32::MAX;
+
+impl<const W: usize, const H: usize> Default for MazeInfo<[[u32; 8]; 2], W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> MazeInfo<[[u32; 8]; 2], W, H> {
+    // DiagonalCostMap
+    pub fn new() -> Self {
+        DiagonalCostMap {
+            grid: [[[[UNREACHABLE; 8]; 2]; W]; H],
+        }
+    }
+
+    pub fn cost(&self, node: &WallNode, heading: Heading) -> u32 {
+        self.grid[node.row][node.col][node.index()][heading as usize]
+    }
+
+    fn cost_mut(&mut self, node: &WallNode, heading: Heading) -> &mut u32 {
+        &mut self.grid[node.row][node.col][node.index()][heading as usize]
+    }
+
+    // Dijkstra's algorithm from the nodes entering the goal, following the moves backwards.
+    pub fn calc_cost_map(
+        &mut self,
+        maze: &impl MazeStorage<W, H>,
+        params: &DiagonalCostParams,
+        goal: &Goal,
+    ) {
+        let mut items = [[[[0u16; 8]; 2]; W]; H];
+        let mut positions = [[[[0u16; 8]; 2]; W]; H];
+        let mut heap = Heap::new(
+            items
+                .as_flattened_mut()
+                .as_flattened_mut()
+                .as_flattened_mut(),
+            positions
+                .as_flattened_mut()
+                .as_flattened_mut()
+                .as_flattened_mut(),
+        );
+
+        self.grid = [[[[UNREACHABLE; 8]; 2]; W]; H];
+        for position in goal.cells() {
+            for side in [
+                Direction::North,
+                Direction::East,
+                Direction::South,
+                Direction::West,
+            ] {
+                let node = match WallNode::of::<W, H>(position.row, position.col, side) {
+                    Some(node) if node.wall(maze) == Wall::Absent => node,
+                    _ => continue,
+                };
+                for heading in HEADINGS {
+                    if node.cell_ahead(heading).map(|(cell, _)| cell) == Some(*position) {
+                        *self.cost_mut(&node, heading) = 0;
+                        heap.push(state_index::<W>(&node, heading), self.keys());
+                    }
+                }
+            }
+        }
+
+        while let Some(state) = heap.pop(self.keys()) {
+            let (node, heading) = state_of::<W>(state);
+            let cost = self.cost(&node, heading) + params.moving(heading);
+
+            // The move to `node` crossed `cell`, entering it from the wall on `entry`
+            let (cell, exit) = match node.cell_behind(heading) {
+                Some(behind) => behind,
+                None => continue,
+            };
+            let entry = match (heading.vertical(), heading.horizontal()) {
+                (Some(v), Some(h)) if v == exit => opposite(h),
+                (Some(v), Some(_)) => opposite(v),
+                _ => opposite(exit),
+            };
+            let from = match WallNode::of::<W, H>(cell.row, cell.col, entry) {
+                Some(from) if from.wall(maze) == Wall::Absent => from,
+                _ => continue,
+            };
+
+            for from_heading in HEADINGS {
+                if from.cell_ahead(from_heading).map(|(c, _)| c) != Some(cell) {
+                    continue;
+                }
+                let from_cost = match params.turn(from_heading, heading) {
+                    Some(turn) => cost + turn,
+                    None => continue,
+                };
+                if from_cost < self.cost(&from, from_heading) {
+                    *self.cost_mut(&from, from_heading) = from_cost;
+                    heap.push(state_index::<W>(&from, from_heading), self.keys());
+                }
+            }
+        }
+    }
+
+    // Minimum cost path from the center of `start`, facing `heading`, to the goal the cost map
+    // was calculated for. Turning in the start cell costs as much as a 90 degree change of heading
+    // per 90 degrees. None if the goal cannot be reached.
+    pub fn path(
+        &self,
+        maze: &impl MazeStorage<W, H>,
+        params: &DiagonalCostParams,
+        start: Position,
+        heading: Direction,
+    ) -> Option<DiagonalPath<W, H>> {
+        let mut path = DiagonalPath::new(start, heading);
+
+        // Leave the start cell
+        let mut best: Option<(u32, WallNode, Heading)> = None;
+        for angle in [0, 2, -2, 4] {
+            let exit = Heading::from(heading).rotate(angle);
+            let node = match WallNode::of::<W, H>(start.row, start.col, exit.to_direction()?) {
+                Some(node) if node.wall(maze) == Wall::Absent => node,
+                _ => continue,
+            };
+            if self.cost(&node, exit) == UNREACHABLE {
+                continue;
+            }
+            let cost = self.cost(&node, exit)
+                + params.straight / 2
+                + params.turn90 * angle.unsigned_abs() as u32 / 2;
+            if best.is_none_or(|(min_cost, _, _)| cost < min_cost) {
+                best = Some((cost, node, exit));
+            }
+        }
+        let (_, mut node, mut heading) = best?;
+        path.push(heading);
+
+        while self.cost(&node, heading) != 0 {
+            let (cell, entry) = node.cell_ahead(heading)?;
+            let mut best: Option<(u32, WallNode, Heading)> = None;
+            for angle in [0, 1, -1, 2, -2] {
+                let next_heading = heading.rotate(angle);
+                let exit = match exit_side(entry, next_heading) {
+                    Some(exit) => exit,
+                    None => continue,
+                };
+                let next = match WallNode::of::<W, H>(cell.row, cell.col, exit) {
+                    Some(next) if next.wall(maze) == Wall::Absent => next,
+                    _ => continue,
+                };
+                if self.cost(&next, next_heading) == UNREACHABLE {
+                    continue;
+                }
+                let turn = match params.turn(heading, next_heading) {
+                    Some(turn) => turn,
+                    None => continue,
+                };
+                let cost = self.cost(&next, next_heading) + params.moving(next_heading) + turn;
+                if best.is_none_or(|(min_cost, _, _)| cost < min_cost) {
+                    best = Some((cost, next, next_heading));
+                }
+            }
+            (_, node, heading) = best?;
+            if !path.push(heading) {
+                return None;
+            }
+        }
+        Some(path)
+    }
+
+    fn keys(&self) -> &[u32] {
+        self.grid.as_flattened().as_flattened().as_flattened()
+    }
+}
+
+fn state_index<const W: usize>(node: &WallNode, heading: Heading) -> usize {
+    ((node.row * W + node.col) * 2 + node.index()) * 8 + heading as usize
+}
+
+fn state_of<const W: usize>(state: usize) -> (WallNode, Heading) {
+    let cell = state / 16;
+    let side = if (state / 8).is_multiple_of(2) {
+        Direction::North
+    } else {
+        Direction::West
+    };
+    (
+        WallNode {
+            row: cell / W,
+            col: cell % W,
+            side,
+        },
+        HEADINGS[state % 8],
+    )
+}
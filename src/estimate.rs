@@ -0,0 +1,245 @@
+use super::diagonal::DiagonalPath;
+use super::maze::{Direction, Goal, MazeStorage, Position};
+use super::motion::{compile, Motion, TurnSet};
+use super::route::Route;
+use super::solver::{StepMap, StepMapMode};
+
+/*
+    Run time estimation.
+
+    Straight and diagonal runs follow a trapezoidal velocity profile: the mouse accelerates,
+    runs at the maximum speed if the run is long enough and decelerates to the speed of the next
+    turn. Turns take a fixed time and are entered and left at a fixed speed.
+    The mouse stands still at the start and at the end.
+
+    Lengths are in meters, speeds in m/s and times in seconds.
+*/
+
+// f32::sqrt needs std. Without it, Newton's method starts from halving the exponent, which is
+// within 6% of the root, and reaches full precision in four steps.
+#[cfg(feature = "std")]
+fn sqrt(x: f32) -> f32 {
+    x.sqrt()
+}
+
+#[cfg(not(feature = "std"))]
+fn sqrt(x: f32) -> f32 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    let mut y = f32::from_bits((x.to_bits() >> 1) + 0x1fc0_0000);
+    for _ in 0..4 {
+        y = 0.5 * (y + x / y);
+    }
+    y
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TurnTiming {
+    pub time: f32,
+    // Speed at the entry and the exit of the turn
+    pub speed: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunParams {
+    // Length of a side of a cell, 0.18 for classic and 0.09 for half-size mazes
+    pub cell_length: f32,
+    pub acceleration: f32,
+    pub max_speed: f32,
+    pub small_turn90: TurnTiming,
+    pub large_turn90: TurnTiming,
+    pub large_turn180: TurnTiming,
+    pub diagonal45: TurnTiming,
+    pub diagonal135: TurnTiming,
+    pub v90: TurnTiming,
+    // Time of the turns in place
+    pub pivot90: f32,
+    pub pivot180: f32,
+}
+
+impl Default for RunParams {
+    fn default() -> Self {
+        RunParams {
+            cell_length: 0.18,
+            acceleration: 3.0,
+            max_speed: 2.0,
+            small_turn90: TurnTiming {
+                time: 0.45,
+                speed: 0.3,
+            },
+            large_turn90: TurnTiming {
+                time: 0.45,
+                speed: 0.6,
+            },
+            large_turn180: TurnTiming {
+                time: 0.55,
+                speed: 0.6,
+            },
+            diagonal45: TurnTiming {
+                time: 0.3,
+                speed: 0.6,
+            },
+            diagonal135: TurnTiming {
+                time: 0.45,
+                speed: 0.6,
+            },
+            v90: TurnTiming {
+                time: 0.3,
+                speed: 0.6,
+            },
+            pivot90: 0.3,
+            pivot180: 0.5,
+        }
+    }
+}
+
+impl RunParams {
+    // Time and speed of a turn. Straight runs give None.
+    fn turn(&self, motion: Motion) -> Option<TurnTiming> {
+        let still = |time| TurnTiming { time, speed: 0.0 };
+        match motion {
+            Motion::Straight(_) | Motion::Diagonal(_) => None,
+            Motion::Pivot90(_) => Some(still(self.pivot90)),
+            Motion::Pivot180 => Some(still(self.pivot180)),
+            Motion::SmallTurn90(_) => Some(self.small_turn90),
+            Motion::LargeTurn90(_) => Some(self.large_turn90),
+            Motion::LargeTurn180(_) => Some(self.large_turn180),
+            Motion::DiagonalIn45(_) | Motion::DiagonalOut45(_) => Some(self.diagonal45),
+            Motion::DiagonalIn135(_) | Motion::DiagonalOut135(_) => Some(self.diagonal135),
+            Motion::V90(_) => Some(self.v90),
+        }
+    }
+
+    fn length(&self, motion: Motion) -> f32 {
+        match motion {
+            Motion::Straight(n) => n as f32 * self.cell_length / 2.0,
+            Motion::Diagonal(n) => n as f32 * self.cell_length * core::f32::consts::FRAC_1_SQRT_2,
+            _ => 0.0,
+        }
+    }
+
+    // Time to run `length` starting at `entry` and ending at `exit` speed
+    pub fn straight_time(&self, length: f32, entry: f32, exit: f32) -> f32 {
+        let a = self.acceleration;
+        if length <= 0.0 {
+            return 0.0;
+        }
+        // Too short to change the speed as needed, so the mouse only accelerates or decelerates
+        if (exit * exit - entry * entry).abs() >= 2.0 * a * length {
+            let reached = if exit > entry {
+                sqrt(entry * entry + 2.0 * a * length)
+            } else {
+                sqrt((entry * entry - 2.0 * a * length).max(0.0))
+            };
+            return (reached - entry).abs() / a;
+        }
+        let peak = sqrt((2.0 * a * length + entry * entry + exit * exit) / 2.0);
+        if peak <= self.max_speed {
+            return (peak - entry) / a + (peak - exit) / a;
+        }
+        let v = self.max_speed;
+        let accelerating = (v * v - entry * entry) / (2.0 * a);
+        let decelerating = (v * v - exit * exit) / (2.0 * a);
+        (v - entry) / a + (v - exit) / a + (length - accelerating - decelerating) / v
+    }
+}
+
+// Estimated time to run the motions
+pub fn estimate_time(motions: &[Motion], params: &RunParams) -> f32 {
+    let mut time = 0.0;
+    let mut speed = 0.0;
+    let mut length = 0.0;
+
+    for &motion in motions {
+        match params.turn(motion) {
+            Some(turn) => {
+                time += params.straight_time(length, speed, turn.speed) + turn.time;
+                speed = turn.speed;
+                length = 0.0;
+            }
+            None => length += params.length(motion),
+        }
+    }
+    time + params.straight_time(length, speed, 0.0)
+}
+
+// Estimated time to run the route with the turns of `turn_set`.
+// None if the route turns back in a cell after the start or cannot be compiled.
+pub fn estimate_route<const W: usize, const H: usize>(
+    route: &Route<W, H>,
+    turn_set: TurnSet,
+    params: &RunParams,
+) -> Option<f32> {
+    let path = DiagonalPath::from_route(route)?;
+    Some(estimate_time(compile(&path, turn_set)?.as_slice(), params))
+}
+
+// Among at most `max_candidates` shortest routes through the known walls of `maze`,
+// the route with the lowest estimated time and that time.
+pub fn fastest_route<const W: usize, const H: usize>(
+    maze: &impl MazeStorage<W, H>,
+    goal: &Goal,
+    start: Position,
+    heading: Direction,
+    turn_set: TurnSet,
+    params: &RunParams,
+    max_candidates: usize,
+) -> Option<(Route<W, H>, f32)> {
+    let mut stepmap = StepMap::<W, H>::new();
+    let mut best: Option<(Route<W, H>, f32)> = None;
+
+    stepmap.calc_step_map(maze, StepMapMode::UnexploredAsPresent, goal);
+    stepmap.for_each_route(
+        maze,
+        StepMapMode::UnexploredAsPresent,
+        start,
+        heading,
+        max_candidates,
+        |route| {
+            if let Some(time) = estimate_route(route, turn_set, params) {
+                if best.as_ref().is_none_or(|(_, best_time)| time < *best_time) {
+                    best = Some((route.clone(), time));
+                }
+            }
+        },
+    );
+    best
+}
+
+#[cfg(all(test, not(feature = "std")))]
+mod tests {
+    use super::sqrt;
+
+    fn assert_close(x: f32, expected: f32) {
+        let root = sqrt(x);
+        assert!(
+            (root - expected).abs() <= expected * 1e-6,
+            "sqrt({}) = {}, expected {}",
+            x,
+            root,
+            expected
+        );
+    }
+
+    #[test]
+    fn square_roots() {
+        assert_eq!(sqrt(0.0), 0.0);
+        assert_eq!(sqrt(-1.0), 0.0);
+        assert_close(1.0, 1.0);
+        assert_close(2.0, core::f32::consts::SQRT_2);
+        assert_close(0.5, core::f32::consts::FRAC_1_SQRT_2);
+        assert_close(0.27, 0.519_615_2);
+        assert_close(1e-6, 1e-3);
+        assert_close(1e6, 1e3);
+    }
+
+    // Perfect squares over the range of lengths and speeds of a run
+    #[test]
+    fn roots_of_squares() {
+        for i in 1..2000 {
+            let y = i as f32 * 0.01;
+            assert_close(y * y, y);
+        }
+    }
+}
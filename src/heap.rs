@@ -0,0 +1,136 @@
+// Binary min-heap of state indices on top of buffers owned by the caller.
+// The key of a state is looked up in `keys`, so lowering a key is done in place and every state is
+// in the heap at most once. `items` and `positions` must be as long as `keys`.
+pub(crate) struct Heap<'a> {
+    items: &'a mut [u16],
+    positions: &'a mut [u16],
+    len: usize,
+}
+
+const NOT_IN_HEAP: u16 = u16::MAX;
+
+impl<'a> Heap<'a> {
+    pub fn new(items: &'a mut [u16], positions: &'a mut [u16]) -> Self {
+        positions.iter_mut().for_each(|p| *p = NOT_IN_HEAP);
+        Heap {
+            items,
+            positions,
+            len: 0,
+        }
+    }
+
+    // Adds the state, or moves it up after its key was lowered.
+    pub fn push(&mut self, state: usize, keys: &[u32]) {
+        let position = match self.positions[state] {
+            NOT_IN_HEAP => {
+                self.items[self.len] = state as u16;
+                self.positions[state] = self.len as u16;
+                self.len += 1;
+                self.len - 1
+            }
+            position => position as usize,
+        };
+        self.sift_up(position, keys);
+    }
+
+    // Removes the state with the smallest key.
+    pub fn pop(&mut self, keys: &[u32]) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+        let state = self.items[0] as usize;
+        self.len -= 1;
+        self.swap(0, self.len);
+        self.positions[state] = NOT_IN_HEAP;
+        self.sift_down(0, keys);
+        Some(state)
+    }
+
+    fn sift_up(&mut self, mut position: usize, keys: &[u32]) {
+        while position > 0 {
+            let parent = (position - 1) / 2;
+            if keys[self.items[parent] as usize] <= keys[self.items[position] as usize] {
+                break;
+            }
+            self.swap(parent, position);
+            position = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut position: usize, keys: &[u32]) {
+        loop {
+            let mut smallest = position;
+            for child in [position * 2 + 1, position * 2 + 2] {
+                if child < self.len
+                    && keys[self.items[child] as usize] < keys[self.items[smallest] as usize]
+                {
+                    smallest = child;
+                }
+            }
+            if smallest == position {
+                break;
+            }
+            self.swap(smallest, position);
+            position = smallest;
+        }
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.items.swap(a, b);
+        self.positions[self.items[a] as usize] = a as u16;
+        self.positions[self.items[b] as usize] = b as u16;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // The tests run without the std feature too.
+    extern crate std;
+    use std::vec::Vec;
+
+    use super::*;
+
+    fn drain(heap: &mut Heap, keys: &[u32]) -> Vec<usize> {
+        let mut states = Vec::new();
+        while let Some(state) = heap.pop(keys) {
+            states.push(state);
+        }
+        states
+    }
+
+    #[test]
+    fn pops_in_key_order() {
+        let keys = [50, 10, 40, 0, 30, 20, 60, 10];
+        let mut items = [0; 8];
+        let mut positions = [0; 8];
+        let mut heap = Heap::new(&mut items, &mut positions);
+        for state in [6, 2, 0, 4, 7, 1, 5, 3] {
+            heap.push(state, &keys);
+        }
+        let popped = drain(&mut heap, &keys);
+        let sorted: Vec<u32> = popped.iter().map(|&s| keys[s]).collect();
+        assert_eq!(sorted, [0, 10, 10, 20, 30, 40, 50, 60]);
+        assert_eq!(heap.pop(&keys), None);
+    }
+
+    #[test]
+    fn lowered_key_moves_up() {
+        let mut keys = [50, 40, 30, 20, 10];
+        let mut items = [0; 5];
+        let mut positions = [0; 5];
+        let mut heap = Heap::new(&mut items, &mut positions);
+        for state in 0..5 {
+            heap.push(state, &keys);
+        }
+        keys[0] = 5;
+        heap.push(0, &keys);
+        keys[2] = 15;
+        heap.push(2, &keys);
+        // Each state is in the heap once
+        assert_eq!(drain(&mut heap, &keys), [0, 4, 2, 3, 1]);
+
+        // A popped state can be pushed again
+        heap.push(3, &keys);
+        assert_eq!(heap.pop(&keys), Some(3));
+    }
+}
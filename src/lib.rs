@@ -1,2 +1,22 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/*
+    Maze solver for micromice.
+
+    Without the default "std" feature the crate is no_std, and it never allocates: mazes, step
+    maps, queues, routes and motion lists are arrays whose sizes follow from the maze size, so
+    they fit on the stack of a microcontroller.
+*/
+
+pub mod cost_map;
+pub mod diagonal;
+pub mod estimate;
+mod heap;
 pub mod maze;
+pub mod motion;
+mod queue;
+pub mod route;
+pub mod rules;
+pub mod search;
+pub mod sim;
 pub mod solver;
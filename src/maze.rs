@@ -1,5 +1,5 @@
 /*
-    - The start is north-west of the maze. The position is (X, Y) = (0, 0)
+    - The default start is north-west of the maze. The position is (X, Y) = (0, 0)
     - MAZE consists of CELLs, CELLs have WALLs.
 
     - Direction
@@ -15,20 +15,31 @@
         Y
 
     - Initial position
-        -> (X:0, Y:0, Heading:East)
+        -> (X:0, Y:0, Heading:East) by default, or any Start given to Maze::with_start()
 */
 
+use core::fmt::{self, Write};
+
+pub mod maz;
+pub mod packed;
+pub mod text;
+pub mod validate;
+
+// Size of the classic maze, used as the default width and height.
 pub const MAZE_SIZE: usize = 16;
 
-// The start cell is fixed. Those values are basically used to initialize the wall to the right of the start cell.
+// The default start cell. Another start can be given with Maze::with_start() and MazeConfig.
 const MAZE_START_Y: usize = 0;
 const MAZE_START_X: usize = 0;
 
-pub struct MazeInfo<T> {
-    pub grid: [[T; MAZE_SIZE]; MAZE_SIZE],
+// W is the number of columns (X) and H the number of rows (Y).
+// The defaults describe the classic 16x16 maze.
+#[derive(Clone)]
+pub struct MazeInfo<T, const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> {
+    pub grid: [[T; W]; H],
 }
 
-impl<T> MazeInfo<T> {
+impl<T, const W: usize, const H: usize> MazeInfo<T, W, H> {
     pub fn get(&self, row: usize, col: usize) -> &T {
         &self.grid[row][col]
     }
@@ -47,14 +58,14 @@ impl<T> MazeInfo<T> {
                 }
             }
             Direction::East => {
-                if col == MAZE_SIZE {
+                if col + 1 >= W {
                     None
                 } else {
                     Some(self.get(row, col + 1))
                 }
             }
             Direction::South => {
-                if row == MAZE_SIZE {
+                if row + 1 >= H {
                     None
                 } else {
                     Some(self.get(row + 1, col))
@@ -85,14 +96,14 @@ impl<T> MazeInfo<T> {
                 }
             }
             Direction::East => {
-                if col == MAZE_SIZE {
+                if col + 1 >= W {
                     None
                 } else {
                     Some(self.get_mut(row, col + 1))
                 }
             }
             Direction::South => {
-                if row == MAZE_SIZE {
+                if row + 1 >= H {
                     None
                 } else {
                     Some(self.get_mut(row + 1, col))
@@ -109,6 +120,7 @@ impl<T> MazeInfo<T> {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Position {
     pub row: usize,
     pub col: usize,
@@ -141,6 +153,156 @@ impl Position {
     }
 }
 
+// Start cell and the heading of the mouse in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Start {
+    pub position: Position,
+    pub heading: Direction,
+}
+
+impl Default for Start {
+    fn default() -> Self {
+        Start {
+            position: Position::new(MAZE_START_Y, MAZE_START_X),
+            heading: Direction::East,
+        }
+    }
+}
+
+// Panics unless the start is inside a W x H maze and the mouse can leave it straight ahead.
+pub(crate) fn assert_start<const W: usize, const H: usize>(start: &Start) {
+    let Position { row, col } = start.position;
+    assert!(row < H && col < W, "start outside of the maze");
+    assert!(
+        match start.heading {
+            Direction::North => row > 0,
+            Direction::East => col + 1 < W,
+            Direction::South => row + 1 < H,
+            Direction::West => col > 0,
+        },
+        "start heading into the outer wall"
+    );
+}
+
+// The goal may consist of several cells, e.g. the 2x2 centre of the classic maze.
+// The cells are kept in a fixed-size array.
+pub const GOAL_CAPACITY: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Goal {
+    cells: [Position; GOAL_CAPACITY],
+    len: usize,
+}
+
+impl Default for Goal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Goal {
+    // Empty goal. Add cells with `push`.
+    pub const fn new() -> Self {
+        Goal {
+            cells: [Position::new(0, 0); GOAL_CAPACITY],
+            len: 0,
+        }
+    }
+
+    // Single cell goal
+    pub const fn cell(position: Position) -> Self {
+        let mut goal = Goal::new();
+        goal.cells[0] = position;
+        goal.len = 1;
+        goal
+    }
+
+    // Rectangle of `height` x `width` cells whose north-west corner is `top_left`.
+    // None if the rectangle has more than GOAL_CAPACITY cells.
+    pub fn rect(top_left: Position, height: usize, width: usize) -> Option<Self> {
+        let mut goal = Goal::new();
+        for row in top_left.row..top_left.row + height {
+            for col in top_left.col..top_left.col + width {
+                if !goal.push(Position::new(row, col)) {
+                    return None;
+                }
+            }
+        }
+        Some(goal)
+    }
+
+    // Centre of a W x H maze: the 2x2 cells of the classic maze, or the middle cell or
+    // cells when a side is odd.
+    pub fn center<const W: usize, const H: usize>() -> Self {
+        // At most 4 cells
+        Goal::rect(
+            Position::new((H - 1) / 2, (W - 1) / 2),
+            2 - H % 2,
+            2 - W % 2,
+        )
+        .unwrap()
+    }
+
+    // Goal made of arbitrary cells. None if there are more than GOAL_CAPACITY distinct cells.
+    pub fn from_cells(cells: &[Position]) -> Option<Self> {
+        let mut goal = Goal::new();
+        for &position in cells {
+            if !goal.push(position) {
+                return None;
+            }
+        }
+        Some(goal)
+    }
+
+    // Returns false if the goal is full. A cell already in the goal is not added twice.
+    pub fn push(&mut self, position: Position) -> bool {
+        if self.contains(position.row, position.col) {
+            return true;
+        }
+        if self.len == GOAL_CAPACITY {
+            return false;
+        }
+        self.cells[self.len] = position;
+        self.len += 1;
+        true
+    }
+
+    // The goal is reached when the mouse enters any of its cells.
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        self.cells().iter().any(|p| p.row == row && p.col == col)
+    }
+
+    pub fn cells(&self) -> &[Position] {
+        &self.cells[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+// Start and goal of a maze, which the walls do not tell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MazeConfig {
+    pub start: Start,
+    pub goal: Goal,
+}
+
+impl MazeConfig {
+    pub const fn new(start: Start, goal: Goal) -> Self {
+        MazeConfig { start, goal }
+    }
+
+    // The default start and the centre of a W x H maze as the goal
+    pub fn classic<const W: usize, const H: usize>() -> Self {
+        MazeConfig::new(Start::default(), Goal::center::<W, H>())
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Wall {
     Present,
@@ -148,7 +310,7 @@ pub enum Wall {
     Unexplored,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
     North,
     East,
@@ -214,6 +376,10 @@ pub fn fblr_to_nsew(facing: Direction, direction: DirectionOfTravel) -> Directio
     }
 }
 
+pub fn opposite(direction: Direction) -> Direction {
+    fblr_to_nsew(direction, DirectionOfTravel::Backward)
+}
+
 pub fn nsew_to_index(direction: Direction) -> (isize, isize) {
     match direction {
         Direction::North => (0, -1),
@@ -238,6 +404,14 @@ pub const TOZAINANBOKU: [Direction; 4] = [
     Direction::North,
 ];
 
+// Same order as the discriminants of Direction
+pub const NESW: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Facing {
     Forward,
@@ -254,6 +428,12 @@ pub struct Cell {
     pub west: Wall,
 }
 
+impl Default for Cell {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Cell {
     pub const fn new() -> Self {
         Cell {
@@ -274,29 +454,65 @@ impl Cell {
     }
 }
 
-impl MazeInfo<Cell> {
-    // Maze
+// Wall storage of a maze, so that the solvers run on either MazeInfo<Cell>, which keeps the
+// four walls of every cell, or packed::PackedMaze, which keeps every wall once in two bits.
+// The walls around the maze cannot be changed by set_wall.
+pub trait MazeStorage<const W: usize, const H: usize> {
+    fn get_cell(&self, row: usize, col: usize) -> Cell;
+    fn set_wall(&mut self, row: usize, col: usize, direction: Direction, wall: Wall);
+}
+
+impl<const W: usize, const H: usize> MazeStorage<W, H> for MazeInfo<Cell, W, H> {
+    fn get_cell(&self, row: usize, col: usize) -> Cell {
+        *MazeInfo::get_cell(self, row, col)
+    }
+
+    fn set_wall(&mut self, row: usize, col: usize, direction: Direction, wall: Wall) {
+        MazeInfo::set_wall(self, row, col, direction, wall)
+    }
+}
+
+impl<const W: usize, const H: usize> Default for MazeInfo<Cell, W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> MazeInfo<Cell, W, H> {
+    // Maze with the default start
     pub fn new() -> Self {
-        let mut grid = [[Cell::new(); MAZE_SIZE]; MAZE_SIZE];
+        Self::with_start(&Start::default())
+    }
+
+    // Panics if the start is outside of the maze or faces the outer wall.
+    pub fn with_start(start: &Start) -> Self {
+        assert_start::<W, H>(start);
+        let mut grid = [[Cell::new(); W]; H];
 
         // Set the walls around the maze
-        for i in 0..MAZE_SIZE {
-            grid[0][i].north = Wall::Present;
-            grid[MAZE_SIZE - 1][i].south = Wall::Present;
-            grid[i][0].west = Wall::Present;
-            grid[i][MAZE_SIZE - 1].east = Wall::Present;
+        for cell in grid[0].iter_mut() {
+            cell.north = Wall::Present;
+        }
+        for cell in grid[H - 1].iter_mut() {
+            cell.south = Wall::Present;
+        }
+        for row in grid.iter_mut() {
+            row[0].west = Wall::Present;
+            row[W - 1].east = Wall::Present;
         }
 
-        let mut maze = MazeInfo { grid: grid };
+        let mut maze = MazeInfo { grid };
 
         // The starting cell is walled off except for the front.
-        maze.set_wall2(
-            MAZE_START_Y,
-            MAZE_START_X,
-            Direction::East,
-            Facing::Right,
-            Wall::Present,
-        );
+        for facing in [Facing::Right, Facing::Backward, Facing::Left] {
+            maze.set_wall2(
+                start.position.row,
+                start.position.col,
+                start.heading,
+                facing,
+                Wall::Present,
+            );
+        }
 
         maze
     }
@@ -347,13 +563,13 @@ impl MazeInfo<Cell> {
                 }
             }
             Direction::East => {
-                if col < MAZE_SIZE - 1 {
+                if col + 1 < W {
                     self.grid[row][col].east = wall;
                     self.grid[row][col + 1].west = wall;
                 }
             }
             Direction::South => {
-                if row < MAZE_SIZE - 1 {
+                if row + 1 < H {
                     self.grid[row][col].south = wall;
                     self.grid[row + 1][col].north = wall;
                 }
@@ -371,89 +587,107 @@ impl MazeInfo<Cell> {
         &self.grid[row][col]
     }
 
-    pub fn lines_iter(&self, goal_x: usize, goal_y: usize) -> MazeLinesIter {
-        MazeLinesIter::new(self, goal_x, goal_y)
+    // Lines from north to south with cells three characters wide, which text::parse() reads.
+    pub fn lines_iter<'a>(&'a self, config: &'a MazeConfig) -> MazeLinesIter<'a, W, H> {
+        MazeLinesIter::new(self, config)
     }
 }
 
-pub struct MazeLinesIter<'a> {
-    maze_info: &'a MazeInfo<Cell>,
-    goal_x: usize,
-    goal_y: usize,
+pub struct MazeLinesIter<'a, const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> {
+    maze_info: &'a MazeInfo<Cell, W, H>,
+    config: &'a MazeConfig,
     current_line: usize,
     is_finished: bool,
 }
 
-impl<'a> MazeLinesIter<'a> {
-    fn new(maze_info: &'a MazeInfo<Cell>, goal_x: usize, goal_y: usize) -> Self {
+impl<'a, const W: usize, const H: usize> MazeLinesIter<'a, W, H> {
+    fn new(maze_info: &'a MazeInfo<Cell, W, H>, config: &'a MazeConfig) -> Self {
         MazeLinesIter {
             maze_info,
-            goal_x,
-            goal_y,
-            current_line: MAZE_SIZE * 2,
+            config,
+            current_line: 0,
             is_finished: false,
         }
     }
 }
 
-impl<'a> Iterator for MazeLinesIter<'a> {
-    type Item = String;
+impl<'a, const W: usize, const H: usize> Iterator for MazeLinesIter<'a, W, H> {
+    type Item = MazeLine<'a, W, H>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.is_finished {
             return None;
         }
 
-        let mut line = String::new();
-        let y = self.current_line / 2;
+        let line = MazeLine {
+            maze_info: self.maze_info,
+            config: self.config,
+            line: self.current_line,
+        };
 
-        if self.current_line % 2 == 0 {
+        if self.current_line == H * 2 {
+            self.is_finished = true;
+        } else {
+            self.current_line += 1;
+        }
+
+        Some(line)
+    }
+}
+
+// A line of the maze, which is written by Display.
+pub struct MazeLine<'a, const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> {
+    maze_info: &'a MazeInfo<Cell, W, H>,
+    config: &'a MazeConfig,
+    line: usize,
+}
+
+impl<const W: usize, const H: usize> fmt::Display for MazeLine<'_, W, H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let y = self.line / 2;
+
+        if self.line.is_multiple_of(2) {
             // Top wall of cells
-            for x in 0..MAZE_SIZE {
-                line.push('+');
-                if y < MAZE_SIZE {
-                    line.push_str(match self.maze_info.grid[y][x].north {
-                        Wall::Present => "---",
-                        Wall::Absent => "   ",
-                        Wall::Unexplored => "...",
-                    });
+            for x in 0..W {
+                f.write_char('+')?;
+                // The last line is the bottom edge of the maze
+                let wall = if y < H {
+                    self.maze_info.grid[y][x].north
                 } else {
-                    // This is the bottom edge of the maze
-                    line.push_str("---");
-                }
+                    self.maze_info.grid[H - 1][x].south
+                };
+                f.write_str(match wall {
+                    Wall::Present => "---",
+                    Wall::Absent => "   ",
+                    Wall::Unexplored => "...",
+                })?;
             }
-            line.push('+');
+            f.write_char('+')
         } else {
             // Sides of cells
-            for x in 0..MAZE_SIZE {
-                line.push(match self.maze_info.grid[y][x].west {
+            for x in 0..W {
+                f.write_char(match self.maze_info.grid[y][x].west {
                     Wall::Present => '|',
                     Wall::Absent => ' ',
                     Wall::Unexplored => ':',
-                });
-                if self.goal_x == x && self.goal_y == y {
-                    line.push_str(" G ");
-                } else if MAZE_START_X == x && MAZE_START_Y == y {
-                    line.push_str(" S ");
+                })?;
+                if self.config.goal.contains(y, x) {
+                    f.write_str(" G ")?;
+                } else if self.config.start.position == Position::new(y, x)
+                    && text::marks_start(self.maze_info, self.config)
+                {
+                    f.write_str(" S ")?;
                 } else {
-                    line.push_str("   ");
+                    f.write_str("   ")?;
                 }
             }
-            line.push(match self.maze_info.grid[y][MAZE_SIZE - 1].east {
+            f.write_char(match self.maze_info.grid[y][W - 1].east {
                 Wall::Present => '|',
                 Wall::Absent => ' ',
                 Wall::Unexplored => ':',
-            });
+            })
         }
-
-        if self.current_line == 0 {
-            self.is_finished = true;
-        } else {
-            self.current_line -= 1;
-        }
-
-        Some(line)
     }
 }
 
-pub type Maze = MazeInfo<Cell>;
+pub type Maze<const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> = MazeInfo<Cell, W, H>;
@@ -0,0 +1,99 @@
+use core::fmt;
+
+use super::{Direction, Maze, Wall, TOZAINANBOKU};
+
+/*
+    The binary .maz format of the micromouse maze archives.
+
+    One byte per cell with the wall bits N = 1, E = 2, S = 4 and W = 8. The archives put the
+    start in the south-west corner heading north, and store the cells column by column from
+    the start, i.e. the byte of (x, y) is at x * (number of rows) + y, with y counting north.
+
+    The maze of this crate starts in the north-west corner heading east, so the format is
+    turned a quarter clockwise: north of the format is east here, east is south and so on.
+    Then the cells are stored row by row, the byte of (row, col) at row * W + col.
+
+    The format carries no goal, so the caller chooses it, e.g. Goal::center().
+*/
+
+const NORTH: u8 = 1;
+const EAST: u8 = 2;
+const SOUTH: u8 = 4;
+const WEST: u8 = 8;
+
+// Bit of the wall on the side of the cell in this crate's orientation
+const fn bit(direction: Direction) -> u8 {
+    match direction {
+        Direction::North => WEST,
+        Direction::East => NORTH,
+        Direction::South => EAST,
+        Direction::West => SOUTH,
+    }
+}
+
+// The data is not W * H bytes long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthError {
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl fmt::Display for LengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Expected {} bytes of maze data, found {}",
+            self.expected, self.found
+        )
+    }
+}
+
+impl core::error::Error for LengthError {}
+
+// A wall is present if either cell next to it has its bit set.
+// The walls around the maze and the wall right of the start are always present.
+pub fn parse<const W: usize, const H: usize>(bytes: &[u8]) -> Result<Maze<W, H>, LengthError> {
+    if bytes.len() != W * H {
+        return Err(LengthError {
+            expected: W * H,
+            found: bytes.len(),
+        });
+    }
+
+    let mut maze = Maze::<W, H>::new();
+    for row in 0..H {
+        for col in 0..W {
+            let byte = bytes[row * W + col];
+            for direction in TOZAINANBOKU {
+                if byte & bit(direction) != 0 {
+                    maze.set_wall(row, col, direction, Wall::Present);
+                }
+            }
+        }
+    }
+    Ok(maze)
+}
+
+// Writes W * H bytes to `out`. Unexplored walls are written as absent.
+pub fn write<const W: usize, const H: usize>(
+    maze: &Maze<W, H>,
+    out: &mut [u8],
+) -> Result<(), LengthError> {
+    if out.len() != W * H {
+        return Err(LengthError {
+            expected: W * H,
+            found: out.len(),
+        });
+    }
+
+    for row in 0..H {
+        for col in 0..W {
+            let cell = maze.get(row, col);
+            out[row * W + col] = TOZAINANBOKU
+                .iter()
+                .filter(|&&d| cell.get(d) == Wall::Present)
+                .fold(0, |byte, &d| byte | bit(d));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use super::{
+    assert_start, fblr_to_nsew, Cell, Direction, DirectionOfTravel, Maze, MazeStorage, Start, Wall,
+    MAZE_SIZE,
+};
+
+/*
+    Maze storage for small RAM, keeping every wall once in two bits.
+
+    The walls inside the maze are packed densely into N bytes: first the walls east of the cells,
+    row by row, then the walls south of them. The walls around the maze are not stored, as
+    set_wall cannot change them. N is packed_len(W, H), which Rust cannot yet compute from W and
+    H in the type, so it is a parameter of its own and checked at compile time.
+
+        16x16: 120 bytes instead of 1024 for MazeInfo<Cell>
+        32x32: 496 bytes instead of 4096
+*/
+
+const ABSENT: u8 = 0;
+const PRESENT: u8 = 1;
+const UNEXPLORED: u8 = 2;
+
+const fn encode(wall: Wall) -> u8 {
+    match wall {
+        Wall::Absent => ABSENT,
+        Wall::Present => PRESENT,
+        Wall::Unexplored => UNEXPLORED,
+    }
+}
+
+const fn decode(bits: u8) -> Wall {
+    match bits {
+        ABSENT => Wall::Absent,
+        PRESENT => Wall::Present,
+        _ => Wall::Unexplored,
+    }
+}
+
+// Bytes of the walls inside a W x H maze
+pub const fn packed_len(w: usize, h: usize) -> usize {
+    let walls = (w - 1) * h + w * (h - 1);
+    (walls * 2).div_ceil(8)
+}
+
+// e.g. PackedMaze<32, 32, { packed_len(32, 32) }> for the half-size maze
+pub struct PackedMaze<
+    const W: usize = MAZE_SIZE,
+    const H: usize = MAZE_SIZE,
+    const N: usize = { packed_len(MAZE_SIZE, MAZE_SIZE) },
+> {
+    bytes: [u8; N],
+}
+
+impl<const W: usize, const H: usize, const N: usize> Default for PackedMaze<W, H, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize, const N: usize> PackedMaze<W, H, N> {
+    // Maze with the default start
+    pub fn new() -> Self {
+        Self::with_start(&Start::default())
+    }
+
+    // Panics if the start is outside of the maze or faces the outer wall.
+    pub fn with_start(start: &Start) -> Self {
+        assert_start::<W, H>(start);
+        let mut maze = Self::absent();
+
+        // The starting cell is walled off except for the front.
+        for travel in [
+            DirectionOfTravel::Right,
+            DirectionOfTravel::Backward,
+            DirectionOfTravel::Left,
+        ] {
+            maze.set_wall(
+                start.position.row,
+                start.position.col,
+                fblr_to_nsew(start.heading, travel),
+                Wall::Present,
+            );
+        }
+
+        maze
+    }
+
+    // Maze without inner walls. Every constructor starts from it, so that none of them can
+    // make a maze whose walls do not fit in N bytes.
+    fn absent() -> Self {
+        const {
+            assert!(
+                N == packed_len(W, H),
+                "PackedMaze needs N = packed_len(W, H)"
+            )
+        };
+        PackedMaze { bytes: [0; N] }
+    }
+
+    // Index of the wall inside the maze at (row, col, direction), None for the walls around it
+    fn index(row: usize, col: usize, direction: Direction) -> Option<usize> {
+        let east = |row: usize, col: usize| row * (W - 1) + col;
+        let south = |row: usize, col: usize| (W - 1) * H + row * W + col;
+        match direction {
+            Direction::North if row > 0 => Some(south(row - 1, col)),
+            Direction::East if col + 1 < W => Some(east(row, col)),
+            Direction::South if row + 1 < H => Some(south(row, col)),
+            Direction::West if col > 0 => Some(east(row, col - 1)),
+            _ => None,
+        }
+    }
+
+    pub fn get_wall(&self, row: usize, col: usize, direction: Direction) -> Wall {
+        match Self::index(row, col, direction) {
+            Some(i) => decode((self.bytes[i / 4] >> (i % 4 * 2)) & 3),
+            None => Wall::Present,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, const N: usize> MazeStorage<W, H> for PackedMaze<W, H, N> {
+    fn get_cell(&self, row: usize, col: usize) -> Cell {
+        Cell {
+            north: self.get_wall(row, col, Direction::North),
+            east: self.get_wall(row, col, Direction::East),
+            south: self.get_wall(row, col, Direction::South),
+            west: self.get_wall(row, col, Direction::West),
+        }
+    }
+
+    fn set_wall(&mut self, row: usize, col: usize, direction: Direction, wall: Wall) {
+        if let Some(i) = Self::index(row, col, direction) {
+            let shift = i % 4 * 2;
+            self.bytes[i / 4] = (self.bytes[i / 4] & !(3 << shift)) | (encode(wall) << shift);
+        }
+    }
+}
+
+// The walls around `maze` are taken to be present. Inconsistent walls are taken from the
+// cell north or west of them.
+impl<const W: usize, const H: usize, const N: usize> From<&Maze<W, H>> for PackedMaze<W, H, N> {
+    fn from(maze: &Maze<W, H>) -> Self {
+        let mut packed = Self::absent();
+        for row in 0..H {
+            for col in 0..W {
+                let cell = maze.get(row, col);
+                packed.set_wall(row, col, Direction::East, cell.east);
+                packed.set_wall(row, col, Direction::South, cell.south);
+            }
+        }
+        packed
+    }
+}
+
+impl<const W: usize, const H: usize, const N: usize> From<&PackedMaze<W, H, N>> for Maze<W, H> {
+    fn from(packed: &PackedMaze<W, H, N>) -> Self {
+        let mut maze = Maze::new();
+        for row in 0..H {
+            for col in 0..W {
+                *maze.get_mut(row, col) = packed.get_cell(row, col);
+            }
+        }
+        maze
+    }
+}
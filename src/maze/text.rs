@@ -0,0 +1,359 @@
+use core::fmt;
+
+use super::{Cell, Direction, Goal, Maze, MazeConfig, Position, Start, Wall, NESW};
+
+/*
+    Parser for the text format of the mazes in bin/assets.
+
+        +-+-+      +---+---+
+        |G  |      | G     |
+        + +-+      +   +---+
+        |S  |      | S     |
+        +-+-+      +---+---+
+
+    Pillars are '+', walls are '-' and '|', unexplored walls are '.' and ':', and walls
+    which are not drawn are absent. Every cell marked 'G' is part of the goal. 'S' marks the
+    start. The mouse starts heading out of its only side without a wall, or east if it can
+    when there are several. Without 'S' the start is the default one.
+
+    write() leaves 'S' out for the default start when its heading cannot be told from the walls,
+    so that what it writes parses back to the same start.
+
+    Cells are one character wide as in the assets, or three as printed by lines_iter().
+    The cell width and the number of columns are detected from the first line, the number
+    of rows from the number of lines. Whitespace after the last wall of a line is ignored.
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    // Line and column start at 1
+    UnexpectedCharacter {
+        expected: &'static str,
+        found: char,
+        line: usize,
+        column: usize,
+    },
+    TooManyColumns {
+        line: usize,
+        column: usize,
+    },
+    TooFewColumns {
+        line: usize,
+    },
+    // The text ends within a row of cells
+    UnexpectedEnd {
+        line: usize,
+    },
+    // The text describes a maze of another size
+    SizeMismatch {
+        width: usize,
+        height: usize,
+    },
+    MissingGoal,
+    // The goal has more than GOAL_CAPACITY cells
+    TooManyGoals {
+        line: usize,
+        column: usize,
+    },
+    DuplicateStart {
+        line: usize,
+        column: usize,
+    },
+    // The start is walled on every side
+    StartWalledIn {
+        line: usize,
+        column: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedCharacter {
+                expected,
+                found,
+                line,
+                column,
+            } => write!(
+                f,
+                "{}:{}: Expected {}, found '{}'",
+                line,
+                column,
+                expected,
+                found.escape_default()
+            ),
+            ParseError::TooManyColumns { line, column } => {
+                write!(f, "{}:{}: Too many columns in maze", line, column)
+            }
+            ParseError::TooFewColumns { line } => write!(f, "{}: Too few columns in maze", line),
+            ParseError::UnexpectedEnd { line } => write!(f, "{}: Unexpected end of maze", line),
+            ParseError::SizeMismatch { width, height } => {
+                write!(f, "Unexpected maze size {}x{}", width, height)
+            }
+            ParseError::MissingGoal => write!(f, "No goal in maze"),
+            ParseError::TooManyGoals { line, column } => {
+                write!(f, "{}:{}: Too many goal cells", line, column)
+            }
+            ParseError::DuplicateStart { line, column } => {
+                write!(f, "{}:{}: More than one start", line, column)
+            }
+            ParseError::StartWalledIn { line, column } => {
+                write!(
+                    f,
+                    "{}:{}: Start must be open on at least one side",
+                    line, column
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    // Number of columns and rows of cells
+    pub width: usize,
+    pub height: usize,
+    // Characters between two pillars, 1 or 3
+    pub cell_width: usize,
+}
+
+// Detects the layout of the maze, so that the caller can choose W and H for parse().
+pub fn detect(s: &str) -> Result<Layout, ParseError> {
+    let first = s
+        .lines()
+        .next()
+        .ok_or(ParseError::UnexpectedEnd { line: 1 })?
+        .trim_end();
+    let mut chars = first.chars();
+    match chars.next() {
+        Some('+') => {}
+        Some(c) => {
+            return Err(ParseError::UnexpectedCharacter {
+                expected: "+",
+                found: c,
+                line: 1,
+                column: 1,
+            })
+        }
+        None => return Err(ParseError::UnexpectedEnd { line: 1 }),
+    }
+    let cell_width = match chars.position(|c| c == '+') {
+        Some(n @ (1 | 3)) => n,
+        Some(n) => {
+            return Err(ParseError::UnexpectedCharacter {
+                expected: "cells 1 or 3 characters wide",
+                found: '+',
+                line: 1,
+                column: n + 2,
+            })
+        }
+        None => return Err(ParseError::TooFewColumns { line: 1 }),
+    };
+
+    let lines = s.lines().count();
+    if lines.is_multiple_of(2) {
+        return Err(ParseError::UnexpectedEnd { line: lines + 1 });
+    }
+    Ok(Layout {
+        width: (first.chars().count() - 1) / (cell_width + 1),
+        height: lines / 2,
+        cell_width,
+    })
+}
+
+pub fn parse<const W: usize, const H: usize>(
+    s: &str,
+) -> Result<(Maze<W, H>, MazeConfig), ParseError> {
+    let layout = detect(s)?;
+    if layout.width != W || layout.height != H {
+        return Err(ParseError::SizeMismatch {
+            width: layout.width,
+            height: layout.height,
+        });
+    }
+    let period = layout.cell_width + 1;
+
+    let mut maze = Maze::<W, H>::new();
+    let mut goal = Goal::new();
+    // Start cell with the line and column of its marker
+    let mut start = None;
+    for (index, text) in s.lines().enumerate() {
+        let line = index + 1;
+        let row = index / 2;
+        let mut segment = ' ';
+        let mut count = 0;
+        for (j, c) in text.chars().enumerate() {
+            count = j + 1;
+            let unexpected = |expected| ParseError::UnexpectedCharacter {
+                expected,
+                found: c,
+                line,
+                column: j + 1,
+            };
+            let col = j / period;
+            let offset = j % period;
+            if col > W || (col == W && offset > 0) {
+                if text.chars().skip(j).all(char::is_whitespace) {
+                    break;
+                }
+                return Err(ParseError::TooManyColumns {
+                    line,
+                    column: j + 1,
+                });
+            }
+
+            if index % 2 == 0 {
+                // Pillars and the walls north of the cells
+                if offset == 0 {
+                    if c != '+' {
+                        return Err(unexpected("+"));
+                    }
+                    continue;
+                }
+                let wall = match c {
+                    '-' => Wall::Present,
+                    ' ' => Wall::Absent,
+                    '.' => Wall::Unexplored,
+                    _ => return Err(unexpected("-, . or Space")),
+                };
+                if offset == 1 {
+                    segment = c;
+                } else if c != segment {
+                    return Err(unexpected("the same wall"));
+                }
+                if offset == 1 {
+                    if row == H {
+                        maze.set_wall(H - 1, col, Direction::South, wall);
+                    } else {
+                        maze.set_wall(row, col, Direction::North, wall);
+                    }
+                }
+            } else if offset == 0 {
+                // The wall west of the cell, or east of the last one
+                let wall = match c {
+                    '|' => Wall::Present,
+                    ' ' => Wall::Absent,
+                    ':' => Wall::Unexplored,
+                    _ => return Err(unexpected("|, : or Space")),
+                };
+                if col == W {
+                    maze.set_wall(row, W - 1, Direction::East, wall);
+                } else {
+                    maze.set_wall(row, col, Direction::West, wall);
+                }
+            } else if offset == period / 2 {
+                // The middle of the cell
+                match c {
+                    ' ' => {}
+                    'G' => {
+                        if !goal.push(Position::new(row, col)) {
+                            return Err(ParseError::TooManyGoals {
+                                line,
+                                column: j + 1,
+                            });
+                        }
+                    }
+                    'S' => {
+                        if start.is_some() {
+                            return Err(ParseError::DuplicateStart {
+                                line,
+                                column: j + 1,
+                            });
+                        }
+                        start = Some((Position::new(row, col), line, j + 1));
+                    }
+                    _ => return Err(unexpected("G, S or Space")),
+                }
+            } else if c != ' ' {
+                return Err(unexpected("Space"));
+            }
+        }
+        if count < W * period + 1 {
+            return Err(ParseError::TooFewColumns { line });
+        }
+    }
+
+    if goal.is_empty() {
+        return Err(ParseError::MissingGoal);
+    }
+    let start = match start {
+        Some((position, line, column)) => {
+            match start_heading(maze.get(position.row, position.col)) {
+                Some(heading) => Start { position, heading },
+                None => return Err(ParseError::StartWalledIn { line, column }),
+            }
+        }
+        None => Start::default(),
+    };
+    Ok((maze, MazeConfig::new(start, goal)))
+}
+
+// Heading of the mouse in a start cell marked 'S': east if that side has no wall, or else the
+// first such side in NESW order.
+fn start_heading(cell: &Cell) -> Option<Direction> {
+    let default = Start::default().heading;
+    let open = |d: Direction| cell.get(d) != Wall::Present;
+    if open(default) {
+        return Some(default);
+    }
+    NESW.iter().copied().find(|&d| open(d))
+}
+
+// True if 'S' is written for the start. A start other than the default one is always marked,
+// even if parse() would read another heading for it.
+pub(crate) fn marks_start<const W: usize, const H: usize>(
+    maze: &Maze<W, H>,
+    config: &MazeConfig,
+) -> bool {
+    let start = config.start;
+    start != Start::default()
+        || start_heading(maze.get(start.position.row, start.position.col)) == Some(start.heading)
+}
+
+// Writes the maze in the format parse() reads with cells one character wide.
+pub fn write<const W: usize, const H: usize>(
+    out: &mut impl fmt::Write,
+    maze: &Maze<W, H>,
+    config: &MazeConfig,
+) -> fmt::Result {
+    let wall = |row: usize, col: usize, direction, [present, unexplored]: [char; 2]| match maze
+        .get(row, col)
+        .get(direction)
+    {
+        Wall::Present => present,
+        Wall::Absent => ' ',
+        Wall::Unexplored => unexplored,
+    };
+
+    let start = config.start;
+    let mark_start = marks_start(maze, config);
+
+    for row in 0..H {
+        for col in 0..W {
+            write!(out, "+{}", wall(row, col, Direction::North, ['-', '.']))?;
+        }
+        writeln!(out, "+")?;
+        for col in 0..W {
+            let mark = if config.goal.contains(row, col) {
+                'G'
+            } else if mark_start && start.position == Position::new(row, col) {
+                'S'
+            } else {
+                ' '
+            };
+            write!(
+                out,
+                "{}{}",
+                wall(row, col, Direction::West, ['|', ':']),
+                mark
+            )?;
+        }
+        writeln!(out, "{}", wall(row, W - 1, Direction::East, ['|', ':']))?;
+    }
+    for col in 0..W {
+        write!(out, "+{}", wall(H - 1, col, Direction::South, ['-', '.']))?;
+    }
+    writeln!(out, "+")
+}
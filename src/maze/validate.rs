@@ -0,0 +1,202 @@
+use core::fmt;
+
+use super::{
+    fblr_to_nsew, opposite, Direction, DirectionOfTravel, Goal, Maze, MazeConfig, Position, Wall,
+    NESW,
+};
+use crate::solver::{StepMap, StepMapMode};
+
+/*
+    Consistency checks of a maze and its start and goal.
+
+    Walls are stored on both cells next to them, so writing `grid` directly can leave the two
+    sides of a wall disagreeing. Unexplored walls are taken to be absent when looking for
+    unreachable cells.
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Diagnostic {
+    // The wall on `direction` of the cell differs from the wall of the neighbour
+    InconsistentWall {
+        position: Position,
+        direction: Direction,
+    },
+    MissingOuterWall {
+        position: Position,
+        direction: Direction,
+    },
+    UnreachableCell {
+        position: Position,
+    },
+    StartOutOfBounds,
+    // The start must be open to the front only
+    StartNotWalled {
+        direction: Direction,
+    },
+    StartBlocked,
+    EmptyGoal,
+    GoalOutOfBounds {
+        position: Position,
+    },
+    GoalAtStart,
+    UnreachableGoal {
+        position: Position,
+    },
+}
+
+impl Diagnostic {
+    // Unreachable cells are found in contest mazes, so they are only reported.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Diagnostic::UnreachableCell { .. })
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Diagnostic::InconsistentWall {
+                position,
+                direction,
+            } => write!(
+                f,
+                "({}, {}): {:?} wall differs from the wall of the neighbour",
+                position.row, position.col, direction
+            ),
+            Diagnostic::MissingOuterWall {
+                position,
+                direction,
+            } => write!(
+                f,
+                "({}, {}): {:?} outer wall is missing",
+                position.row, position.col, direction
+            ),
+            Diagnostic::UnreachableCell { position } => write!(
+                f,
+                "({}, {}): Cell cannot be reached from the start",
+                position.row, position.col
+            ),
+            Diagnostic::StartOutOfBounds => write!(f, "Start is outside the maze"),
+            Diagnostic::StartNotWalled { direction } => {
+                write!(f, "Start has no {:?} wall", direction)
+            }
+            Diagnostic::StartBlocked => write!(f, "Start is walled in its heading"),
+            Diagnostic::EmptyGoal => write!(f, "Goal has no cells"),
+            Diagnostic::GoalOutOfBounds { position } => write!(
+                f,
+                "({}, {}): Goal is outside the maze",
+                position.row, position.col
+            ),
+            Diagnostic::GoalAtStart => write!(f, "Goal contains the start"),
+            Diagnostic::UnreachableGoal { position } => write!(
+                f,
+                "({}, {}): Goal cannot be reached from the start",
+                position.row, position.col
+            ),
+        }
+    }
+}
+
+impl<const W: usize, const H: usize> Maze<W, H> {
+    // Returns every problem found, in the order of validate_with().
+    #[cfg(feature = "std")]
+    pub fn validate(&self, config: &MazeConfig) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        self.validate_with(config, |d| diagnostics.push(d));
+        diagnostics
+    }
+
+    // Calls `f` with every problem found: the walls cell by cell, then the start and the goal,
+    // then the unreachable cells.
+    pub fn validate_with(&self, config: &MazeConfig, mut f: impl FnMut(Diagnostic)) {
+        for row in 0..H {
+            for col in 0..W {
+                let position = Position::new(row, col);
+                let cell = self.get(row, col);
+                for direction in NESW {
+                    let outer = match direction {
+                        Direction::North => row == 0,
+                        Direction::East => col + 1 == W,
+                        Direction::South => row + 1 == H,
+                        Direction::West => col == 0,
+                    };
+                    if outer {
+                        if cell.get(direction) != Wall::Present {
+                            f(Diagnostic::MissingOuterWall {
+                                position,
+                                direction,
+                            });
+                        }
+                    } else if matches!(direction, Direction::East | Direction::South) {
+                        // Each pair of cells is checked once.
+                        let neighbor = position.neighbor_position(direction);
+                        let other = self
+                            .get(neighbor.row, neighbor.col)
+                            .get(opposite(direction));
+                        if cell.get(direction) != other {
+                            f(Diagnostic::InconsistentWall {
+                                position,
+                                direction,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        let start = config.start;
+        let in_bounds = |p: Position| p.row < H && p.col < W;
+        if !in_bounds(start.position) {
+            f(Diagnostic::StartOutOfBounds);
+        } else {
+            let cell = self.get(start.position.row, start.position.col);
+            for travel in [
+                DirectionOfTravel::Right,
+                DirectionOfTravel::Backward,
+                DirectionOfTravel::Left,
+            ] {
+                let direction = fblr_to_nsew(start.heading, travel);
+                if cell.get(direction) != Wall::Present {
+                    f(Diagnostic::StartNotWalled { direction });
+                }
+            }
+            if cell.get(start.heading) == Wall::Present {
+                f(Diagnostic::StartBlocked);
+            }
+        }
+
+        if config.goal.is_empty() {
+            f(Diagnostic::EmptyGoal);
+        }
+        for &position in config.goal.cells() {
+            if !in_bounds(position) {
+                f(Diagnostic::GoalOutOfBounds { position });
+            } else if position == start.position {
+                f(Diagnostic::GoalAtStart);
+            }
+        }
+
+        if !in_bounds(start.position) {
+            return;
+        }
+        let mut stepmap = StepMap::<W, H>::new();
+        stepmap.calc_step_map(
+            self,
+            StepMapMode::UnexploredAsAbsent,
+            &Goal::cell(start.position),
+        );
+        let reachable = |p: Position| *stepmap.get(p.row, p.col) != 0xFFFE;
+        for &position in config.goal.cells() {
+            if in_bounds(position) && !reachable(position) {
+                f(Diagnostic::UnreachableGoal { position });
+            }
+        }
+        for row in 0..H {
+            for col in 0..W {
+                let position = Position::new(row, col);
+                if !reachable(position) {
+                    f(Diagnostic::UnreachableCell { position });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use super::diagonal::{DiagonalPath, Heading};
+use super::maze::{Direction, MAZE_SIZE};
+
+/*
+    Motion primitives for the motion controller.
+
+    A path is compiled into straight runs and turns. Straight runs are counted in half cells,
+    diagonal runs in half diagonals of a cell, i.e. in moves of a DiagonalPath.
+
+    Search runs only use straight runs, small turns over one cell and turns in place.
+    Fast runs use the large turns, which start and end at the center of a cell, and the diagonal
+    turns:
+        - LargeTurn90    a turn from the center of the cell before the corner to the center of
+                         the cell after it
+        - LargeTurn180   a U-turn over two cells, from a center to a center
+        - DiagonalIn45   from the center of a cell into a diagonal run
+        - DiagonalIn135  from the center of a cell into a diagonal run across the next corner
+        - DiagonalOut45, DiagonalOut135
+                         the same turns backwards, from a diagonal run to the center of a cell
+        - V90            a turn from a diagonal run into the perpendicular diagonal run
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Right,
+    Left,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    // Number of half cells
+    Straight(u16),
+    // Number of half diagonals
+    Diagonal(u16),
+    Pivot90(Side),
+    Pivot180,
+    SmallTurn90(Side),
+    LargeTurn90(Side),
+    LargeTurn180(Side),
+    DiagonalIn45(Side),
+    DiagonalOut45(Side),
+    DiagonalIn135(Side),
+    DiagonalOut135(Side),
+    V90(Side),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurnSet {
+    Search,
+    Fast,
+}
+
+// Compiled motions. A path compiles to less than 4 * W * H motions.
+pub struct MotionList<const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> {
+    motions: [[[Motion; 4]; W]; H],
+    len: usize,
+}
+
+impl<const W: usize, const H: usize> Default for MotionList<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> MotionList<W, H> {
+    pub fn new() -> Self {
+        MotionList {
+            motions: [[[Motion::Pivot180; 4]; W]; H],
+            len: 0,
+        }
+    }
+
+    // Adds the motion, merging straight runs. Returns false if the list is full.
+    pub fn push(&mut self, motion: Motion) -> bool {
+        let merged = match (self.as_slice().last(), motion) {
+            (_, Motion::Straight(0)) | (_, Motion::Diagonal(0)) => return true,
+            (Some(Motion::Straight(a)), Motion::Straight(b)) => Motion::Straight(a + b),
+            (Some(Motion::Diagonal(a)), Motion::Diagonal(b)) => Motion::Diagonal(a + b),
+            _ => {
+                if self.len == W * H * 4 {
+                    return false;
+                }
+                self.len += 1;
+                motion
+            }
+        };
+        self.motions.as_flattened_mut().as_flattened_mut()[self.len - 1] = merged;
+        true
+    }
+
+    pub fn as_slice(&self) -> &[Motion] {
+        &self.motions.as_flattened().as_flattened()[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+// Straight or diagonal run in the same heading.
+// Orthogonal runs are counted in half cells, diagonal runs in moves.
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    heading: Heading,
+    count: u16,
+}
+
+// Compiles the path from the center of the start cell to the center of the goal cell.
+// None if the motions do not fit in the list.
+pub fn compile<const W: usize, const H: usize>(
+    path: &DiagonalPath<W, H>,
+    turn_set: TurnSet,
+) -> Option<MotionList<W, H>> {
+    let mut motions = MotionList::new();
+    let moves = path.moves();
+    let (first, last) = match (moves.first(), path.nodes().last()) {
+        (Some(&first), Some((node, heading))) => (first, (node, heading)),
+        _ => return Some(motions),
+    };
+
+    // Turn in the start cell
+    let pivot = match Heading::from(path.heading()).angle_to(first) {
+        2 => Some(Motion::Pivot90(Side::Right)),
+        -2 => Some(Motion::Pivot90(Side::Left)),
+        4 => Some(Motion::Pivot180),
+        _ => None,
+    };
+    if let Some(pivot) = pivot {
+        push(&mut motions, pivot)?;
+    }
+
+    // Leaving the start cell and entering the goal cell are half cells.
+    // The goal cell is entered across the wall of the last node.
+    let (last_node, last_heading) = last;
+    let enter_goal = match last_node.side {
+        Direction::North => last_heading.vertical(),
+        _ => last_heading.horizontal(),
+    };
+    let mut runs = [[[Run {
+        heading: first,
+        count: 0,
+    }; 2]; W]; H];
+    let runs = runs.as_flattened_mut().as_flattened_mut();
+    let mut len = 0;
+    let halves = |heading: Heading| if heading.is_diagonal() { 1 } else { 2 };
+    for (i, &heading) in moves
+        .iter()
+        .chain(enter_goal.map(Heading::from).iter())
+        .enumerate()
+    {
+        let count = if i == 0 || i == moves.len() {
+            1
+        } else {
+            halves(heading)
+        };
+        if len > 0 && runs[len - 1].heading == heading {
+            runs[len - 1].count += count;
+        } else {
+            runs[len] = Run { heading, count };
+            len += 1;
+        }
+    }
+    let runs = &runs[..len];
+
+    match turn_set {
+        TurnSet::Search => compile_search(runs, &mut motions)?,
+        TurnSet::Fast => compile_fast(runs, &mut motions)?,
+    }
+    Some(motions)
+}
+
+fn push<const W: usize, const H: usize>(
+    motions: &mut MotionList<W, H>,
+    motion: Motion,
+) -> Option<()> {
+    motions.push(motion).then_some(())
+}
+
+fn side(angle: isize) -> Side {
+    if angle > 0 {
+        Side::Right
+    } else {
+        Side::Left
+    }
+}
+
+// Every diagonal move is a small turn in one cell.
+fn compile_search<const W: usize, const H: usize>(
+    runs: &[Run],
+    motions: &mut MotionList<W, H>,
+) -> Option<()> {
+    let mut orthogonal = runs[0].heading;
+    for run in runs {
+        if run.heading.is_diagonal() {
+            for _ in 0..run.count {
+                let side = side(orthogonal.angle_to(run.heading));
+                push(motions, Motion::SmallTurn90(side))?;
+                orthogonal = orthogonal.rotate(if side == Side::Right { 2 } else { -2 });
+            }
+        } else {
+            push(motions, Motion::Straight(run.count))?;
+            orthogonal = run.heading;
+        }
+    }
+    Some(())
+}
+
+fn compile_fast<const W: usize, const H: usize>(
+    runs: &[Run],
+    motions: &mut MotionList<W, H>,
+) -> Option<()> {
+    let is_diagonal = |i: usize| runs.get(i).map(|r: &Run| r.heading.is_diagonal());
+    let is_single = |i: usize| runs.get(i).map(|r: &Run| r.count == 1) == Some(true);
+
+    // Part of the current run already used by the turn before it
+    let mut front = 0;
+    let mut i = 0;
+    while i < runs.len() {
+        let run = runs[i];
+        // Turn after the run, the part of the run it uses and the run after the turn
+        let (turn, back, next, next_front) = if i + 1 == runs.len() {
+            (None, 0, i + 1, 0)
+        } else if !run.heading.is_diagonal() {
+            let angle = |j: usize| run.heading.angle_to(runs[j].heading);
+            if is_single(i + 1) && is_diagonal(i + 2) == Some(false) {
+                (Some(Motion::LargeTurn90(side(angle(i + 2)))), 1, i + 2, 1)
+            } else if is_single(i + 1) && is_single(i + 2) && is_diagonal(i + 3) == Some(false) {
+                (Some(Motion::LargeTurn180(side(angle(i + 1)))), 1, i + 3, 1)
+            } else if is_single(i + 1) && is_diagonal(i + 2) == Some(true) {
+                (Some(Motion::DiagonalIn135(side(angle(i + 1)))), 1, i + 2, 0)
+            } else {
+                (Some(Motion::DiagonalIn45(side(angle(i + 1)))), 1, i + 1, 1)
+            }
+        } else {
+            let angle = |j: usize| run.heading.angle_to(runs[j].heading);
+            if is_diagonal(i + 1) == Some(false) {
+                (Some(Motion::DiagonalOut45(side(angle(i + 1)))), 1, i + 1, 1)
+            } else if is_single(i + 1) && is_diagonal(i + 2) == Some(false) {
+                (
+                    Some(Motion::DiagonalOut135(side(angle(i + 1)))),
+                    0,
+                    i + 2,
+                    1,
+                )
+            } else {
+                (Some(Motion::V90(side(angle(i + 1)))), 0, i + 1, 0)
+            }
+        };
+
+        let count = run.count - front - back;
+        if run.heading.is_diagonal() {
+            push(motions, Motion::Diagonal(count))?;
+        } else {
+            push(motions, Motion::Straight(count))?;
+        }
+        if let Some(turn) = turn {
+            push(motions, turn)?;
+        }
+        front = next_front;
+        i = next;
+    }
+    Some(())
+}
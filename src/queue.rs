@@ -0,0 +1,38 @@
+// FIFO queue on top of a buffer owned by the caller.
+// The capacity follows the buffer, so it can depend on the maze size.
+pub(crate) struct Queue<'a, T> {
+    buffer: &'a mut [T],
+    head: usize,
+    len: usize,
+}
+
+impl<'a, T: Copy> Queue<'a, T> {
+    pub fn new(buffer: &'a mut [T]) -> Self {
+        Queue {
+            buffer,
+            head: 0,
+            len: 0,
+        }
+    }
+
+    // Returns false if the queue is full.
+    pub fn push(&mut self, item: T) -> bool {
+        if self.len == self.buffer.len() {
+            return false;
+        }
+        let tail = (self.head + self.len) % self.buffer.len();
+        self.buffer[tail] = item;
+        self.len += 1;
+        true
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let item = self.buffer[self.head];
+        self.head = (self.head + 1) % self.buffer.len();
+        self.len -= 1;
+        Some(item)
+    }
+}
@@ -0,0 +1,104 @@
+use super::maze::{Direction, Goal, Position, MAZE_SIZE};
+
+// Route from a start cell: the heading at the start, then the direction of every move.
+// A route visits each cell at most once, so W * H moves are enough.
+#[derive(Clone)]
+pub struct Route<const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> {
+    start: Position,
+    heading: Direction,
+    moves: [[Direction; W]; H],
+    len: usize,
+}
+
+impl<const W: usize, const H: usize> Route<W, H> {
+    pub fn new(start: Position, heading: Direction) -> Self {
+        Route {
+            start,
+            heading,
+            moves: [[Direction::North; W]; H],
+            len: 0,
+        }
+    }
+
+    // Builds the route by asking `next_direction` for every move, from `start` facing `heading`
+    // until a goal cell is entered. `next_direction` gets the current cell and heading.
+    // None if `next_direction` gives up or the route gets longer than the maze has cells.
+    pub fn follow<F>(
+        start: Position,
+        heading: Direction,
+        goal: &Goal,
+        mut next_direction: F,
+    ) -> Option<Self>
+    where
+        F: FnMut(Position, Direction) -> Option<Direction>,
+    {
+        let mut route = Route::new(start, heading);
+        let mut position = start;
+        let mut heading = heading;
+
+        while !goal.contains(position.row, position.col) {
+            let direction = next_direction(position, heading)?;
+            if !route.push(direction) {
+                return None;
+            }
+            position = position.neighbor_position(direction);
+            heading = direction;
+        }
+        Some(route)
+    }
+
+    // Returns false if the route is full.
+    pub fn push(&mut self, direction: Direction) -> bool {
+        if self.len == W * H {
+            return false;
+        }
+        self.moves[self.len / W][self.len % W] = direction;
+        self.len += 1;
+        true
+    }
+
+    // Removes the last move
+    pub fn pop(&mut self) -> Option<Direction> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(self.moves[self.len / W][self.len % W])
+    }
+
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    // Heading of the mouse at the start cell, before the first move
+    pub fn heading(&self) -> Direction {
+        self.heading
+    }
+
+    // Directions of the moves, which are also the headings after each move.
+    pub fn moves(&self) -> &[Direction] {
+        &self.moves.as_flattened()[..self.len]
+    }
+
+    // Number of moves
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn end(&self) -> Position {
+        self.iter().last().map(|(p, _)| p).unwrap_or(self.start)
+    }
+
+    // Cells and headings from the start to the end of the route, the start included.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, Direction)> + '_ {
+        let start = (self.start, self.heading);
+        core::iter::once(start).chain(self.moves().iter().scan(start.0, |position, &direction| {
+            *position = position.neighbor_position(direction);
+            Some((*position, direction))
+        }))
+    }
+}
@@ -0,0 +1,250 @@
+use core::fmt;
+
+use super::maze::{
+    fblr_to_nsew, Direction, DirectionOfTravel, Goal, MazeConfig, MazeStorage, Position, Wall,
+};
+use super::solver::{neighbor_index, StepMap, StepMapMode};
+
+/*
+    Contest rules for the design of a maze.
+
+        - The start is in a corner and walled on three sides.
+        - The goal is the 2x2 centre of a classic maze. Half-size mazes may use any goal.
+        - Every pillar touches at least one wall, except the pillars inside the goal.
+        - The goal can be reached from the start.
+        - Neither the left-hand nor the right-hand wall follower reaches the goal.
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    // 16x16 cells of 180mm
+    Classic,
+    // 32x32 cells of 90mm
+    HalfSize,
+}
+
+impl Category {
+    pub const fn size(&self) -> usize {
+        match self {
+            Category::Classic => 16,
+            Category::HalfSize => 32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+impl Hand {
+    // Directions of travel a wall follower tries, in order
+    pub(crate) const fn order(&self) -> [DirectionOfTravel; 4] {
+        match self {
+            Hand::Left => [
+                DirectionOfTravel::Left,
+                DirectionOfTravel::Forward,
+                DirectionOfTravel::Right,
+                DirectionOfTravel::Backward,
+            ],
+            Hand::Right => [
+                DirectionOfTravel::Right,
+                DirectionOfTravel::Forward,
+                DirectionOfTravel::Left,
+                DirectionOfTravel::Backward,
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    WrongSize { width: usize, height: usize },
+    StartNotInCorner,
+    StartNotWalled { direction: Direction },
+    StartBlocked,
+    GoalNotCentered,
+    // A goal cell is outside of the maze
+    GoalOutOfBounds { row: usize, col: usize },
+    // The pillar at the north-west corner of the cell at (row, col)
+    LonelyPillar { row: usize, col: usize },
+    UnreachableGoal,
+    WallFollowerReachesGoal { hand: Hand },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::WrongSize { width, height } => write!(
+                f,
+                "The maze is {}x{}, which is not the size of the category",
+                width, height
+            ),
+            Violation::StartNotInCorner => write!(f, "The start must be in a corner of the maze"),
+            Violation::StartNotWalled { direction } => write!(
+                f,
+                "The start must be walled except for the front, but its {:?} wall is missing",
+                direction
+            ),
+            Violation::StartBlocked => write!(f, "The start must be open in its heading"),
+            Violation::GoalNotCentered => {
+                write!(
+                    f,
+                    "The goal must be the 2x2 cells in the centre of the maze"
+                )
+            }
+            Violation::GoalOutOfBounds { row, col } => {
+                write!(f, "The goal cell ({}, {}) is outside of the maze", row, col)
+            }
+            Violation::LonelyPillar { row, col } => write!(
+                f,
+                "The pillar at ({}, {}) touches no wall, which is only allowed inside the goal",
+                row, col
+            ),
+            Violation::UnreachableGoal => write!(f, "The goal cannot be reached from the start"),
+            Violation::WallFollowerReachesGoal { hand } => write!(
+                f,
+                "Following the wall on the {:?} hand reaches the goal",
+                hand
+            ),
+        }
+    }
+}
+
+// Returns every rule the maze breaks, in the order of check_with().
+#[cfg(feature = "std")]
+pub fn check<const W: usize, const H: usize>(
+    maze: &impl MazeStorage<W, H>,
+    config: &MazeConfig,
+    category: Category,
+) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    check_with(maze, config, category, |v| violations.push(v));
+    violations
+}
+
+// Calls `f` with every rule the maze breaks. The maze is not checked further when its size,
+// start or goal is out of the rules, as the remaining rules depend on them.
+pub fn check_with<const W: usize, const H: usize>(
+    maze: &impl MazeStorage<W, H>,
+    config: &MazeConfig,
+    category: Category,
+    mut f: impl FnMut(Violation),
+) {
+    if W != category.size() || H != category.size() {
+        f(Violation::WrongSize {
+            width: W,
+            height: H,
+        });
+        return;
+    }
+
+    let start = config.start;
+    let Position {
+        row: start_row,
+        col: start_col,
+    } = start.position;
+    if !((start_row == 0 || start_row == H - 1) && (start_col == 0 || start_col == W - 1)) {
+        f(Violation::StartNotInCorner);
+        return;
+    }
+    let cell = maze.get_cell(start_row, start_col);
+    for travel in [
+        DirectionOfTravel::Right,
+        DirectionOfTravel::Backward,
+        DirectionOfTravel::Left,
+    ] {
+        let direction = fblr_to_nsew(start.heading, travel);
+        if cell.get(direction) != Wall::Present {
+            f(Violation::StartNotWalled { direction });
+        }
+    }
+    if cell.get(start.heading) == Wall::Present {
+        f(Violation::StartBlocked);
+    }
+
+    let goal = &config.goal;
+    let center = Goal::center::<W, H>();
+    let centered =
+        goal.len() == center.len() && center.cells().iter().all(|p| goal.contains(p.row, p.col));
+    if category == Category::Classic && !centered {
+        f(Violation::GoalNotCentered);
+        return;
+    }
+    if let Some(p) = goal.cells().iter().find(|p| p.row >= H || p.col >= W) {
+        f(Violation::GoalOutOfBounds {
+            row: p.row,
+            col: p.col,
+        });
+        return;
+    }
+
+    // Interior pillars, from the north-west corner of the cell at (row, col)
+    for row in 1..H {
+        for col in 1..W {
+            let in_goal = goal.contains(row - 1, col - 1)
+                && goal.contains(row - 1, col)
+                && goal.contains(row, col - 1)
+                && goal.contains(row, col);
+            let touches_wall = [
+                maze.get_cell(row - 1, col - 1).east,
+                maze.get_cell(row - 1, col - 1).south,
+                maze.get_cell(row, col).north,
+                maze.get_cell(row, col).west,
+            ]
+            .contains(&Wall::Present);
+            if !in_goal && !touches_wall {
+                f(Violation::LonelyPillar { row, col });
+            }
+        }
+    }
+
+    let mut stepmap = StepMap::<W, H>::new();
+    stepmap.calc_step_map(maze, StepMapMode::UnexploredAsPresent, goal);
+    if *stepmap.get(start_row, start_col) == 0xFFFE {
+        f(Violation::UnreachableGoal);
+        return;
+    }
+
+    for hand in [Hand::Left, Hand::Right] {
+        if follow_wall(maze, config, hand) {
+            f(Violation::WallFollowerReachesGoal { hand });
+        }
+    }
+}
+
+// Returns true if the wall follower on `hand` reaches the goal. It runs until it comes back
+// to a cell in a heading it has already been in.
+pub fn follow_wall<const W: usize, const H: usize>(
+    maze: &impl MazeStorage<W, H>,
+    config: &MazeConfig,
+    hand: Hand,
+) -> bool {
+    let order = hand.order();
+    let mut visited = [[[false; 4]; W]; H];
+    let Position { mut row, mut col } = config.start.position;
+    let mut heading = config.start.heading;
+    while !config.goal.contains(row, col) {
+        if visited[row][col][heading as usize] {
+            return false;
+        }
+        visited[row][col][heading as usize] = true;
+
+        let next = order.iter().find_map(|&travel| {
+            let direction = fblr_to_nsew(heading, travel);
+            if maze.get_cell(row, col).get(direction) != Wall::Absent {
+                return None;
+            }
+            neighbor_index::<W, H>(row, col, direction).map(|n| (direction, n))
+        });
+        match next {
+            Some((direction, (n_row, n_col))) => {
+                heading = direction;
+                (row, col) = (n_row, n_col);
+            }
+            None => return false,
+        }
+    }
+    true
+}
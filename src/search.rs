@@ -0,0 +1,341 @@
+use super::maze::{fblr_to_nsew, Direction, Goal, Maze, Position, Start, Wall, MAZE_SIZE, NESW};
+use super::rules::Hand;
+use super::solver::{
+    follow_step_map, is_shortest_proven, neighbor_index, StepMap, StepMapMode, TieBreak,
+};
+
+/*
+    Search strategies.
+
+    A strategy decides the next move of the mouse from the walls it knows, its cell and heading,
+    and the cells it heads for. It gives None when it stops, e.g. in the goal or when nothing
+    is left to explore.
+
+        Adachi              the neighbor closest to the goal by the step map with unexplored
+                            walls taken as absent
+        WallFollower        the first open side on the left or the right hand
+        FullExploration     the nearest cells with unexplored walls, until there is none
+        ShortestCandidates  the cells with unexplored walls on the shortest route from the
+                            start to the goal, until it is proven shortest
+
+    The mouse knows every wall of the cells it has stood in, so the strategies only move
+    through walls known to be absent.
+*/
+
+pub trait SearchStrategy<const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> {
+    // Direction of the next move. `maze` is what the mouse knows, with the walls of `position`
+    // sensed.
+    fn next_direction(
+        &mut self,
+        maze: &Maze<W, H>,
+        position: Position,
+        heading: Direction,
+        goal: &Goal,
+    ) -> Option<Direction>;
+
+    // Called after the wall at (row, col, direction) of `maze` has been sensed.
+    fn wall_discovered(
+        &mut self,
+        _maze: &Maze<W, H>,
+        _row: usize,
+        _col: usize,
+        _direction: Direction,
+    ) {
+    }
+}
+
+// The step map is kept up to date wall by wall while the goal stays the same.
+pub struct Adachi<const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> {
+    tie_break: TieBreak,
+    stepmap: StepMap<W, H>,
+    // The goal the step map is calculated for
+    goal: Option<Goal>,
+}
+
+impl<const W: usize, const H: usize> Default for Adachi<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> Adachi<W, H> {
+    pub fn new() -> Self {
+        Self::with_tie_break(TieBreak::default())
+    }
+
+    pub fn with_tie_break(tie_break: TieBreak) -> Self {
+        Adachi {
+            tie_break,
+            stepmap: StepMap::new(),
+            goal: None,
+        }
+    }
+
+    pub fn step_map(&self) -> &StepMap<W, H> {
+        &self.stepmap
+    }
+}
+
+impl<const W: usize, const H: usize> SearchStrategy<W, H> for Adachi<W, H> {
+    fn next_direction(
+        &mut self,
+        maze: &Maze<W, H>,
+        position: Position,
+        heading: Direction,
+        goal: &Goal,
+    ) -> Option<Direction> {
+        if self.goal != Some(*goal) {
+            self.stepmap
+                .calc_step_map(maze, StepMapMode::UnexploredAsAbsent, goal);
+            self.goal = Some(*goal);
+        }
+        if *self.stepmap.get(position.row, position.col) == 0 {
+            return None;
+        }
+        follow_step_map(
+            maze,
+            position.row,
+            position.col,
+            heading,
+            &self.tie_break,
+            &self.stepmap,
+        )
+    }
+
+    fn wall_discovered(&mut self, maze: &Maze<W, H>, row: usize, col: usize, direction: Direction) {
+        if self.goal.is_some() {
+            self.stepmap
+                .update_wall(maze, StepMapMode::UnexploredAsAbsent, row, col, direction);
+        }
+    }
+}
+
+// Stops in the goal, or when it comes back to a cell in a heading it has already been in.
+pub struct WallFollower<const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> {
+    hand: Hand,
+    visited: [[[bool; 4]; W]; H],
+}
+
+impl<const W: usize, const H: usize> WallFollower<W, H> {
+    pub fn new(hand: Hand) -> Self {
+        WallFollower {
+            hand,
+            visited: [[[false; 4]; W]; H],
+        }
+    }
+}
+
+impl<const W: usize, const H: usize> SearchStrategy<W, H> for WallFollower<W, H> {
+    fn next_direction(
+        &mut self,
+        maze: &Maze<W, H>,
+        position: Position,
+        heading: Direction,
+        goal: &Goal,
+    ) -> Option<Direction> {
+        let Position { row, col } = position;
+        if goal.contains(row, col) || self.visited[row][col][heading as usize] {
+            return None;
+        }
+        self.visited[row][col][heading as usize] = true;
+        self.hand
+            .order()
+            .iter()
+            .map(|&travel| fblr_to_nsew(heading, travel))
+            .find(|&d| {
+                maze.get(row, col).get(d) == Wall::Absent
+                    && neighbor_index::<W, H>(row, col, d).is_some()
+            })
+    }
+}
+
+// Ignores the goal.
+pub struct FullExploration<const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> {
+    targets: Targets<W, H>,
+}
+
+impl<const W: usize, const H: usize> Default for FullExploration<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> FullExploration<W, H> {
+    pub fn new() -> Self {
+        FullExploration {
+            targets: Targets::new(),
+        }
+    }
+}
+
+impl<const W: usize, const H: usize> SearchStrategy<W, H> for FullExploration<W, H> {
+    fn next_direction(
+        &mut self,
+        maze: &Maze<W, H>,
+        position: Position,
+        heading: Direction,
+        _goal: &Goal,
+    ) -> Option<Direction> {
+        self.targets
+            .next_direction(maze, position, heading, |maze| {
+                nearest_unexplored(maze, position)
+            })
+    }
+}
+
+// Stops once the shortest route from the start to the goal is proven.
+pub struct ShortestCandidates<const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> {
+    start: Start,
+    targets: Targets<W, H>,
+}
+
+impl<const W: usize, const H: usize> ShortestCandidates<W, H> {
+    pub fn new(start: Start) -> Self {
+        ShortestCandidates {
+            start,
+            targets: Targets::new(),
+        }
+    }
+}
+
+impl<const W: usize, const H: usize> SearchStrategy<W, H> for ShortestCandidates<W, H> {
+    fn next_direction(
+        &mut self,
+        maze: &Maze<W, H>,
+        position: Position,
+        heading: Direction,
+        goal: &Goal,
+    ) -> Option<Direction> {
+        if is_shortest_proven(maze, goal, self.start.position) {
+            return None;
+        }
+        let start = self.start;
+        self.targets
+            .next_direction(maze, position, heading, |maze| {
+                shortest_candidates(maze, goal, start)
+            })
+    }
+}
+
+// Cells to explore. The mouse keeps heading for them until it stands in one of them or all their
+// walls are known, so that every choice of the cells leads to at least one more known wall.
+struct Targets<const W: usize, const H: usize> {
+    cells: Goal,
+    stepmap: StepMap<W, H>,
+}
+
+impl<const W: usize, const H: usize> Targets<W, H> {
+    fn new() -> Self {
+        Targets {
+            cells: Goal::new(),
+            stepmap: StepMap::new(),
+        }
+    }
+
+    fn next_direction(
+        &mut self,
+        maze: &Maze<W, H>,
+        position: Position,
+        heading: Direction,
+        choose: impl Fn(&Maze<W, H>) -> Goal,
+    ) -> Option<Direction> {
+        let done = self.cells.contains(position.row, position.col)
+            || !self
+                .cells
+                .cells()
+                .iter()
+                .any(|p| has_unexplored_wall(maze, *p));
+        if done {
+            self.cells = choose(maze);
+        }
+        // New walls may close the cells off, which other cells do not.
+        self.head_for(maze, position, heading).or_else(|| {
+            self.cells = choose(maze);
+            self.head_for(maze, position, heading)
+        })
+    }
+
+    fn head_for(
+        &mut self,
+        maze: &Maze<W, H>,
+        position: Position,
+        heading: Direction,
+    ) -> Option<Direction> {
+        if self.cells.is_empty() {
+            return None;
+        }
+        self.stepmap
+            .calc_step_map(maze, StepMapMode::UnexploredAsAbsent, &self.cells);
+        follow_step_map(
+            maze,
+            position.row,
+            position.col,
+            heading,
+            &TieBreak::default(),
+            &self.stepmap,
+        )
+    }
+}
+
+fn has_unexplored_wall<const W: usize, const H: usize>(
+    maze: &Maze<W, H>,
+    position: Position,
+) -> bool {
+    let cell = maze.get(position.row, position.col);
+    NESW.iter().any(|&d| cell.get(d) == Wall::Unexplored)
+}
+
+// The cells with unexplored walls fewest steps away from `position`
+fn nearest_unexplored<const W: usize, const H: usize>(
+    maze: &Maze<W, H>,
+    position: Position,
+) -> Goal {
+    let mut stepmap = StepMap::<W, H>::new();
+    stepmap.calc_step_map(maze, StepMapMode::UnexploredAsAbsent, &Goal::cell(position));
+
+    let mut nearest = Goal::new();
+    let mut min_step = 0xFFFE;
+    for row in 0..H {
+        for col in 0..W {
+            let step = *stepmap.get(row, col);
+            if step == 0xFFFE
+                || step > min_step
+                || !has_unexplored_wall(maze, Position::new(row, col))
+            {
+                continue;
+            }
+            if step < min_step {
+                min_step = step;
+                nearest = Goal::new();
+            }
+            nearest.push(Position::new(row, col));
+        }
+    }
+    nearest
+}
+
+// Cells with unexplored walls on the shortest route from the start to the goal, taking the
+// unexplored walls as absent. Cells beyond GOAL_CAPACITY are left for later.
+fn shortest_candidates<const W: usize, const H: usize>(
+    maze: &Maze<W, H>,
+    goal: &Goal,
+    start: Start,
+) -> Goal {
+    let mut candidates = Goal::new();
+    let mut stepmap = StepMap::<W, H>::new();
+    stepmap.calc_step_map(maze, StepMapMode::UnexploredAsAbsent, goal);
+    if let Some(route) = stepmap.route(
+        maze,
+        StepMapMode::UnexploredAsAbsent,
+        goal,
+        start.position,
+        start.heading,
+    ) {
+        for (position, _) in route.iter() {
+            if has_unexplored_wall(maze, position) && !candidates.push(position) {
+                break;
+            }
+        }
+    }
+    candidates
+}
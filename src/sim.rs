@@ -0,0 +1,301 @@
+use super::maze::{
+    nsew_to_fblr, Direction, DirectionOfTravel, Goal, Maze, MazeConfig, Position, Wall, MAZE_SIZE,
+    NESW,
+};
+use super::search::{Adachi, SearchStrategy, ShortestCandidates};
+use super::solver::{is_shortest_proven, neighbor_index, StepMap, StepMapMode};
+
+/*
+    Search simulator.
+
+    The mouse knows the walls of a cell once it stands in it. It starts with the walls inside
+    the maze unexplored, except for those of the start cell, and moves as its search strategy
+    decides, Adachi unless another one is given.
+
+    A search run goes to the goal and back to the start. On the way back the step map leads to
+    the start, again with unexplored walls taken as absent, so the mouse tries new cells where
+    they may be shorter.
+
+    The shortest route is proven when taking the unexplored walls as absent does not make it
+    shorter. Until then, the cells of such a route which still have unexplored walls could make
+    it shorter, and the mouse visits them (search::ShortestCandidates).
+*/
+
+// Outcome of a step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Moved(Direction),
+    // The strategy stops with the mouse in the cells it heads for, the goal or the start on
+    // the way back.
+    AtGoal,
+    // The strategy stops elsewhere, e.g. as the walls known so far close the goal off or
+    // nothing is left to explore.
+    Stopped,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SimResult {
+    // The mouse has entered the goal
+    pub reached_goal: bool,
+    // Moves from cell to cell
+    pub steps: usize,
+    // Different cells the mouse has stood in, the start included
+    pub cells_visited: usize,
+    // Turns of 90 degrees
+    pub turns: usize,
+    pub u_turns: usize,
+    // Walls inside the maze which are no longer unexplored
+    pub walls_discovered: usize,
+}
+
+// Cells of the shortest route through the known walls, with unexplored walls taken as absent.
+// The route is known to be shortest once every cell on it is confirmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathCoverage {
+    // Cells of the route, the start and the goal cell included
+    pub cells: usize,
+    // Cells of the route the mouse has stood in, so that all their walls are known
+    pub confirmed: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundTrip {
+    // From the start to the goal
+    pub outbound: SimResult,
+    // From the start to the goal and back
+    pub total: SimResult,
+    pub returned: bool,
+    // None if the goal cannot be reached
+    pub path: Option<PathCoverage>,
+}
+
+pub struct Simulator<const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE, S = Adachi<W, H>> {
+    actual: Maze<W, H>,
+    local: Maze<W, H>,
+    config: MazeConfig,
+    // The cells the mouse heads for
+    target: Goal,
+    strategy: S,
+    visited: [[bool; W]; H],
+    position: Position,
+    heading: Direction,
+    result: SimResult,
+}
+
+impl<const W: usize, const H: usize> Simulator<W, H> {
+    pub fn new(actual: Maze<W, H>, config: MazeConfig) -> Self {
+        Self::with_strategy(actual, config, Adachi::new())
+    }
+
+    pub fn step_map(&self) -> &StepMap<W, H> {
+        self.strategy.step_map()
+    }
+}
+
+impl<const W: usize, const H: usize, S: SearchStrategy<W, H>> Simulator<W, H, S> {
+    pub fn with_strategy(actual: Maze<W, H>, config: MazeConfig, strategy: S) -> Self {
+        let mut local = Maze::with_start(&config.start);
+        for row in 0..H {
+            for col in 0..W {
+                for direction in [Direction::East, Direction::South] {
+                    if local.get(row, col).get(direction) == Wall::Absent {
+                        local.set_wall(row, col, direction, Wall::Unexplored);
+                    }
+                }
+            }
+        }
+        let mut simulator = Simulator {
+            actual,
+            local,
+            config,
+            target: config.goal,
+            strategy,
+            visited: [[false; W]; H],
+            position: config.start.position,
+            heading: config.start.heading,
+            result: SimResult::default(),
+        };
+        simulator.enter(|_, _, _, _| {});
+        simulator
+    }
+
+    // Moves the mouse to the next cell as the strategy decides.
+    pub fn step(&mut self) -> Step {
+        let direction =
+            self.strategy
+                .next_direction(&self.local, self.position, self.heading, &self.target);
+        self.advance(direction, |_, _, _, _| {})
+    }
+
+    // Same as step, but `strategy` decides instead of the strategy of the simulator, which still
+    // learns of the walls sensed.
+    pub fn step_with(&mut self, strategy: &mut impl SearchStrategy<W, H>) -> Step {
+        let direction =
+            strategy.next_direction(&self.local, self.position, self.heading, &self.target);
+        self.advance(direction, |maze, row, col, d| {
+            strategy.wall_discovered(maze, row, col, d)
+        })
+    }
+
+    // A move through a wall not known to be absent stops the mouse as well.
+    fn advance(
+        &mut self,
+        direction: Option<Direction>,
+        discovered: impl FnMut(&Maze<W, H>, usize, usize, Direction),
+    ) -> Step {
+        let Position { row, col } = self.position;
+        let stopped = if self.target.contains(row, col) {
+            Step::AtGoal
+        } else {
+            Step::Stopped
+        };
+        let direction = match direction {
+            Some(direction) if self.local.get(row, col).get(direction) == Wall::Absent => direction,
+            _ => return stopped,
+        };
+        let (n_row, n_col) = match neighbor_index::<W, H>(row, col, direction) {
+            Some(neighbor) => neighbor,
+            None => return stopped,
+        };
+
+        match nsew_to_fblr(self.heading, direction) {
+            DirectionOfTravel::Forward => {}
+            DirectionOfTravel::Right | DirectionOfTravel::Left => self.result.turns += 1,
+            DirectionOfTravel::Backward => self.result.u_turns += 1,
+        }
+        self.position = Position::new(n_row, n_col);
+        self.heading = direction;
+        self.result.steps += 1;
+        self.enter(discovered);
+        Step::Moved(direction)
+    }
+
+    // Steps until the strategy stops, in the goal unless it cannot reach it.
+    pub fn run_to_goal(&mut self) -> SimResult {
+        while let Step::Moved(_) = self.step() {}
+        self.result()
+    }
+
+    // Heads for the start from now on.
+    pub fn return_to_start(&mut self) {
+        self.target = Goal::cell(self.config.start.position);
+    }
+
+    // Runs to the goal and, if it is reached, back to the start.
+    pub fn run_round_trip(&mut self) -> RoundTrip {
+        let outbound = self.run_to_goal();
+        let mut returned = false;
+        if outbound.reached_goal {
+            self.return_to_start();
+            self.run_to_goal();
+            returned = self.position == self.config.start.position;
+        }
+        RoundTrip {
+            outbound,
+            total: self.result(),
+            returned,
+            path: self.path_coverage(),
+        }
+    }
+
+    // Visits the cells that could still make the route from the start to the goal shorter until
+    // the shortest route is proven. Returns false if the mouse cannot reach those cells.
+    pub fn run_until_proven(&mut self) -> bool {
+        let mut strategy = ShortestCandidates::new(self.config.start);
+        let target = self.target;
+        self.target = self.config.goal;
+        while let Step::Moved(_) = self.step_with(&mut strategy) {}
+        self.target = target;
+        self.is_shortest_proven()
+    }
+
+    // Steps as `strategy` decides until it stops.
+    pub fn run_with(&mut self, strategy: &mut impl SearchStrategy<W, H>) -> SimResult {
+        while let Step::Moved(_) = self.step_with(strategy) {}
+        self.result()
+    }
+
+    pub fn is_shortest_proven(&self) -> bool {
+        is_shortest_proven(&self.local, &self.config.goal, self.config.start.position)
+    }
+
+    // Coverage of the shortest route from the start to the goal by the cells visited so far.
+    pub fn path_coverage(&self) -> Option<PathCoverage> {
+        let mut stepmap = StepMap::<W, H>::new();
+        stepmap.calc_step_map(
+            &self.local,
+            StepMapMode::UnexploredAsAbsent,
+            &self.config.goal,
+        );
+        let route = stepmap.route(
+            &self.local,
+            StepMapMode::UnexploredAsAbsent,
+            &self.config.goal,
+            self.config.start.position,
+            self.config.start.heading,
+        )?;
+        let mut coverage = PathCoverage {
+            cells: 0,
+            confirmed: 0,
+        };
+        for (position, _) in route.iter() {
+            coverage.cells += 1;
+            if self.visited[position.row][position.col] {
+                coverage.confirmed += 1;
+            }
+        }
+        Some(coverage)
+    }
+
+    // Senses the walls of the cell the mouse has entered and tells them to the strategies.
+    fn enter(&mut self, mut discovered: impl FnMut(&Maze<W, H>, usize, usize, Direction)) {
+        let Position { row, col } = self.position;
+        if !self.visited[row][col] {
+            self.visited[row][col] = true;
+            self.result.cells_visited += 1;
+        }
+        if self.config.goal.contains(row, col) {
+            self.result.reached_goal = true;
+        }
+        for direction in NESW {
+            if self.local.get(row, col).get(direction) != Wall::Unexplored {
+                continue;
+            }
+            let wall = self.actual.get(row, col).get(direction);
+            self.local.set_wall(row, col, direction, wall);
+            self.strategy
+                .wall_discovered(&self.local, row, col, direction);
+            discovered(&self.local, row, col, direction);
+            self.result.walls_discovered += 1;
+        }
+    }
+
+    pub fn result(&self) -> SimResult {
+        self.result
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    pub fn heading(&self) -> Direction {
+        self.heading
+    }
+
+    pub fn config(&self) -> &MazeConfig {
+        &self.config
+    }
+
+    pub fn actual(&self) -> &Maze<W, H> {
+        &self.actual
+    }
+
+    // The walls the mouse knows
+    pub fn local(&self) -> &Maze<W, H> {
+        &self.local
+    }
+
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+}
@@ -1,8 +1,11 @@
-use core::usize;
+use super::maze::{
+    fblr_to_nsew, opposite, Direction, DirectionOfTravel, Goal, MazeInfo, MazeStorage, Position,
+    Wall, MAZE_SIZE, NESW, TOZAINANBOKU,
+};
+use super::queue::Queue;
+use super::route::Route;
 
-use super::maze::{Direction, Maze, MazeInfo, Wall, MAZE_SIZE, TOZAINANBOKU};
-
-pub type StepMap = MazeInfo<u16>;
+pub type StepMap<const W: usize = MAZE_SIZE, const H: usize = MAZE_SIZE> = MazeInfo<u16, W, H>;
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum StepMapMode {
@@ -10,53 +13,318 @@ pub enum StepMapMode {
     UnexploredAsPresent,
 }
 
-impl MazeInfo<u16> {
+// Choice among the neighbors with the same step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TieBreak {
+    // Every direction of travel once, the preferred first
+    pub order: [DirectionOfTravel; 4],
+    // Neighbors with unexplored walls come before the order.
+    pub prefer_unexplored: bool,
+}
+
+impl Default for TieBreak {
+    // Straight, then right, left and back, so that the mouse turns as little as it can.
+    fn default() -> Self {
+        TieBreak {
+            order: [
+                DirectionOfTravel::Forward,
+                DirectionOfTravel::Right,
+                DirectionOfTravel::Left,
+                DirectionOfTravel::Backward,
+            ],
+            prefer_unexplored: false,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize> Default for MazeInfo<u16, W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> MazeInfo<u16, W, H> {
     // StepMap
     pub fn new() -> Self {
-        let map = StepMap {
-            grid: [[0; MAZE_SIZE]; MAZE_SIZE],
+        StepMap { grid: [[0; W]; H] }
+    }
+
+    pub fn calc_step_map(&mut self, maze: &impl MazeStorage<W, H>, mode: StepMapMode, goal: &Goal) {
+        // Each cell enters the queue at most once, so W * H entries are enough.
+        let mut buffer = [[0u16; W]; H];
+        let mut queue = Queue::new(buffer.as_flattened_mut());
+
+        self.reset(goal);
+        for position in goal.cells() {
+            queue.push((position.row * W + position.col) as u16);
+        }
+
+        // Breadth first search from the goal cells
+        while let Some(index) = queue.pop() {
+            let row = index as usize / W;
+            let col = index as usize % W;
+            let next_step = *self.get(row, col) + 1;
+            for direction in TOZAINANBOKU {
+                if let Some((n_row, n_col)) = neighbor_index::<W, H>(row, col, direction) {
+                    // The wall is seen from the neighbor, as the neighbor is the cell being updated.
+                    if self.can_step(maze, mode, n_row, n_col, opposite(direction))
+                        && *self.get(n_row, n_col) > next_step
+                    {
+                        *self.get_mut(n_row, n_col) = next_step;
+                        queue.push((n_row * W + n_col) as u16);
+                    }
+                }
+            }
+        }
+    }
+
+    // Updates the step map after the wall at (row, col, direction) of `maze` has changed.
+    // Only the cells whose step depends on that wall are re-flooded, and the result is the same as
+    // calc_step_map with the same maze and mode.
+    pub fn update_wall(
+        &mut self,
+        maze: &impl MazeStorage<W, H>,
+        mode: StepMapMode,
+        row: usize,
+        col: usize,
+        direction: Direction,
+    ) {
+        let (n_row, n_col) = match neighbor_index::<W, H>(row, col, direction) {
+            Some(neighbor) => neighbor,
+            None => return,
+        };
+
+        // When the wall was closed, the cell that got its step through it may have lost its way
+        // to the goal. Only one of both cells can depend on the other.
+        let mut in_region = [[false; W]; H];
+        let mut buffer = [[0u16; W]; H];
+        let mut region = Queue::new(buffer.as_flattened_mut());
+        let (seed_row, seed_col) = if *self.get(row, col) > *self.get(n_row, n_col) {
+            (row, col)
+        } else {
+            (n_row, n_col)
         };
+        let seed_step = *self.get(seed_row, seed_col);
+        if seed_step != 0
+            && seed_step != 0xFFFE
+            && !self.is_supported(maze, mode, seed_row, seed_col, &in_region)
+        {
+            in_region[seed_row][seed_col] = true;
+            region.push((seed_row * W + seed_col) as u16);
+        }
 
-        map
+        // Collect the cells that only got their step through the region.
+        // The queue visits the region in increasing steps, so all possible supports of a cell are
+        // known to be in the region or not when the cell is checked.
+        while let Some(index) = region.pop() {
+            let r = index as usize / W;
+            let c = index as usize % W;
+            let step = *self.get(r, c);
+            for d in TOZAINANBOKU {
+                if let Some((next_row, next_col)) = neighbor_index::<W, H>(r, c, d) {
+                    if !in_region[next_row][next_col]
+                        && *self.get(next_row, next_col) == step + 1
+                        && self.can_step(maze, mode, next_row, next_col, opposite(d))
+                        && !self.is_supported(maze, mode, next_row, next_col, &in_region)
+                    {
+                        in_region[next_row][next_col] = true;
+                        region.push((next_row * W + next_col) as u16);
+                    }
+                }
+            }
+        }
+
+        // The region is flooded again from its border. Both cells of the wall are flooded from as
+        // well, which lowers the steps when the wall was opened.
+        let mut in_queue = [[false; W]; H];
+        let mut buffer = [[0u16; W]; H];
+        let mut queue = Queue::new(buffer.as_flattened_mut());
+        for r in 0..H {
+            for c in 0..W {
+                if !in_region[r][c] {
+                    continue;
+                }
+                self.grid[r][c] = 0xFFFE;
+                for d in TOZAINANBOKU {
+                    if let Some((next_row, next_col)) = neighbor_index::<W, H>(r, c, d) {
+                        if !in_region[next_row][next_col] && !in_queue[next_row][next_col] {
+                            in_queue[next_row][next_col] = true;
+                            queue.push((next_row * W + next_col) as u16);
+                        }
+                    }
+                }
+            }
+        }
+        for (r, c) in [(row, col), (n_row, n_col)] {
+            if !in_queue[r][c] {
+                in_queue[r][c] = true;
+                queue.push((r * W + c) as u16);
+            }
+        }
+
+        while let Some(index) = queue.pop() {
+            let r = index as usize / W;
+            let c = index as usize % W;
+            in_queue[r][c] = false;
+            let step = *self.get(r, c);
+            if step == 0xFFFE {
+                continue;
+            }
+            for d in TOZAINANBOKU {
+                if let Some((next_row, next_col)) = neighbor_index::<W, H>(r, c, d) {
+                    if self.can_step(maze, mode, next_row, next_col, opposite(d))
+                        && *self.get(next_row, next_col) > step + 1
+                    {
+                        *self.get_mut(next_row, next_col) = step + 1;
+                        if !in_queue[next_row][next_col] {
+                            in_queue[next_row][next_col] = true;
+                            queue.push((next_row * W + next_col) as u16);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // True if the step of (row, col) can be taken from a neighbor outside of the region.
+    fn is_supported(
+        &self,
+        maze: &impl MazeStorage<W, H>,
+        mode: StepMapMode,
+        row: usize,
+        col: usize,
+        in_region: &[[bool; W]; H],
+    ) -> bool {
+        let step = *self.get(row, col);
+        TOZAINANBOKU
+            .iter()
+            .any(|&d| match neighbor_index::<W, H>(row, col, d) {
+                Some((r, c)) => {
+                    !in_region[r][c]
+                        && *self.get(r, c) + 1 == step
+                        && self.can_step(maze, mode, row, col, d)
+                }
+                None => false,
+            })
     }
-    pub fn calc_step_map(&mut self, maze: &Maze, mode: StepMapMode, goal_x: usize, goal_y: usize) {
-        let mut no_cell_updated: bool;
-        no_cell_updated = false;
 
-        fn no_wall_present(mode: StepMapMode, wall: Wall) -> bool {
-            match mode {
-                StepMapMode::UnexploredAsAbsent => wall == Wall::Absent || wall == Wall::Unexplored,
-                StepMapMode::UnexploredAsPresent => wall == Wall::Absent,
+    // True if the step of (row, col) can be taken from its neighbor in `direction`.
+    // As in calc_step_map, the wall is seen from (row, col).
+    fn can_step(
+        &self,
+        maze: &impl MazeStorage<W, H>,
+        mode: StepMapMode,
+        row: usize,
+        col: usize,
+        direction: Direction,
+    ) -> bool {
+        no_wall_present(mode, maze.get_cell(row, col).get(direction))
+    }
+
+    // Shortest route from `start` facing `heading` to `goal`, walking down the step map.
+    // The step map must have been calculated for `goal` with `mode`. Going straight is preferred
+    // when several neighbors are one step closer. None if the goal cannot be reached.
+    pub fn route(
+        &self,
+        maze: &impl MazeStorage<W, H>,
+        mode: StepMapMode,
+        goal: &Goal,
+        start: Position,
+        heading: Direction,
+    ) -> Option<Route<W, H>> {
+        Route::follow(start, heading, goal, |position, heading| {
+            let step = *self.get(position.row, position.col);
+            if step == 0 || step == 0xFFFE {
+                return None;
             }
+            core::iter::once(heading).chain(TOZAINANBOKU).find(|&d| {
+                self.can_step(maze, mode, position.row, position.col, d)
+                    && self.get_neighbor(position.row, position.col, d) == Some(&(step - 1))
+            })
+        })
+    }
+
+    // Calls `f` with shortest routes from `start` facing `heading` to `goal`, at most
+    // `max_routes` of them. The step map must have been calculated for `goal` with `mode`.
+    // Returns the number of routes.
+    pub fn for_each_route<F>(
+        &self,
+        maze: &impl MazeStorage<W, H>,
+        mode: StepMapMode,
+        start: Position,
+        heading: Direction,
+        max_routes: usize,
+        mut f: F,
+    ) -> usize
+    where
+        F: FnMut(&Route<W, H>),
+    {
+        let mut route = Route::new(start, heading);
+        let mut position = start;
+        let mut count = 0;
+        // Index in NESW of the next direction to try from `position`
+        let mut next = 0;
+
+        if *self.get(start.row, start.col) == 0xFFFE {
+            return 0;
         }
+        // Depth first search on the cells one step closer to the goal
+        while count < max_routes {
+            let step = *self.get(position.row, position.col);
+            let found = if step == 0 {
+                f(&route);
+                count += 1;
+                None
+            } else {
+                NESW[next..].iter().copied().find(|&d| {
+                    self.can_step(maze, mode, position.row, position.col, d)
+                        && self.get_neighbor(position.row, position.col, d) == Some(&(step - 1))
+                })
+            };
+            match found {
+                Some(d) => {
+                    route.push(d);
+                    position = position.neighbor_position(d);
+                    next = 0;
+                }
+                None => match route.pop() {
+                    Some(d) => {
+                        position = position.neighbor_position(opposite(d));
+                        next = d as usize + 1;
+                    }
+                    None => break,
+                },
+            }
+        }
+        count
+    }
 
-        self.grid.iter_mut().for_each(|i| {
-            i.iter_mut().for_each(|j| {
-                *j = 0xFFFE; // Not set to 0xFFFF because it may be +1
-            });
-        });
+    // Reference implementation that relaxes the whole grid until nothing changes.
+    // It is kept to check and benchmark calc_step_map.
+    pub fn calc_step_map_by_relaxation(
+        &mut self,
+        maze: &impl MazeStorage<W, H>,
+        mode: StepMapMode,
+        goal: &Goal,
+    ) {
+        let mut no_cell_updated: bool;
+        no_cell_updated = false;
 
-        *self.get_mut(goal_x, goal_y) = 0;
+        self.reset(goal);
 
         while !no_cell_updated {
             no_cell_updated = true;
-            for i in 0..MAZE_SIZE {
-                for j in 0..MAZE_SIZE {
+            for i in 0..H {
+                for j in 0..W {
                     for direction in TOZAINANBOKU {
-                        if no_wall_present(mode, maze.get_cell(i, j).get(direction)) {
-                            match maze.get_neighbor(i, j, direction) {
-                                Some(_) => {
-                                    let neighbor;
-                                    {
-                                        neighbor = *self.get_neighbor(i, j, direction).unwrap();
-                                    }
-                                    let current = self.get_mut(i, j);
-                                    if *current > (neighbor + 1) {
-                                        *current = neighbor + 1;
-                                        no_cell_updated = false;
-                                    }
-                                }
-                                None => (),
+                        if no_wall_present(mode, maze.get_cell(i, j).get(direction))
+                            && self.get_neighbor(i, j, direction).is_some()
+                        {
+                            let neighbor = *self.get_neighbor(i, j, direction).unwrap();
+                            let current = self.get_mut(i, j);
+                            if *current > (neighbor + 1) {
+                                *current = neighbor + 1;
+                                no_cell_updated = false;
                             }
                         }
                     }
@@ -64,36 +332,113 @@ impl MazeInfo<u16> {
             }
         }
     }
+
+    fn reset(&mut self, goal: &Goal) {
+        self.grid.iter_mut().for_each(|i| {
+            i.iter_mut().for_each(|j| {
+                *j = 0xFFFE; // Not set to 0xFFFF because it may be +1
+            });
+        });
+
+        // Every goal cell is a destination
+        for position in goal.cells() {
+            *self.get_mut(position.row, position.col) = 0;
+        }
+    }
+}
+
+fn no_wall_present(mode: StepMapMode, wall: Wall) -> bool {
+    match mode {
+        StepMapMode::UnexploredAsAbsent => wall == Wall::Absent || wall == Wall::Unexplored,
+        StepMapMode::UnexploredAsPresent => wall == Wall::Absent,
+    }
+}
+
+pub(crate) fn neighbor_index<const W: usize, const H: usize>(
+    row: usize,
+    col: usize,
+    direction: Direction,
+) -> Option<(usize, usize)> {
+    match direction {
+        Direction::North if row > 0 => Some((row - 1, col)),
+        Direction::East if col + 1 < W => Some((row, col + 1)),
+        Direction::South if row + 1 < H => Some((row + 1, col)),
+        Direction::West if col > 0 => Some((row, col - 1)),
+        _ => None,
+    }
+}
+
+// Direction of the next move from (row, col) facing `heading`, with the step map calculated
+// for `goal` on `maze` first.
+pub fn decide_direction<const W: usize, const H: usize>(
+    maze: &impl MazeStorage<W, H>,
+    goal: &Goal,
+    row: usize,
+    col: usize,
+    heading: Direction,
+    tie_break: &TieBreak,
+    stepmap: &mut StepMap<W, H>,
+) -> Option<Direction> {
+    stepmap.calc_step_map(maze, StepMapMode::UnexploredAsAbsent, goal);
+    follow_step_map(maze, row, col, heading, tie_break, stepmap)
 }
 
-pub fn decide_direction(
-    maze: &Maze,
-    goal_x: usize,
-    goal_y: usize,
+// Same as decide_direction, but uses the step map as it is.
+// The step map must be up to date with `maze` in StepMapMode::UnexploredAsAbsent,
+// e.g. kept so by StepMap::update_wall.
+pub fn follow_step_map<const W: usize, const H: usize>(
+    maze: &impl MazeStorage<W, H>,
     row: usize,
     col: usize,
-    stepmap: &mut StepMap,
+    heading: Direction,
+    tie_break: &TieBreak,
+    stepmap: &StepMap<W, H>,
 ) -> Option<Direction> {
-    stepmap.calc_step_map(maze, StepMapMode::UnexploredAsAbsent, goal_x, goal_y);
-    let mut min_step: u16 = 0xFFFE;
-    let mut direction_to_go: Direction = Direction::North;
+    // Step of the neighbor, whether it is preferred as unexplored, and its direction
+    let mut best: Option<(u16, bool, Direction)> = None;
 
     if *stepmap.get(row, col) == 0xFFFE {
         return None;
     }
 
-    for d in TOZAINANBOKU {
-        if maze.get_cell(row, col).get(d) == Wall::Absent {
-            match stepmap.get_neighbor(row, col, d) {
-                Some(step) => {
-                    if *step < min_step {
-                        min_step = *step;
-                        direction_to_go = d;
-                    }
-                }
-                None => {}
-            };
+    for travel in tie_break.order {
+        let d = fblr_to_nsew(heading, travel);
+        if maze.get_cell(row, col).get(d) != Wall::Absent {
+            continue;
+        }
+        let (n_row, n_col) = match neighbor_index::<W, H>(row, col, d) {
+            Some(neighbor) => neighbor,
+            None => continue,
+        };
+        let step = *stepmap.get(n_row, n_col);
+        let unexplored = tie_break.prefer_unexplored
+            && NESW
+                .iter()
+                .any(|&nd| maze.get_cell(n_row, n_col).get(nd) == Wall::Unexplored);
+        let better = match best {
+            Some((min_step, min_unexplored, _)) => {
+                step < min_step || (step == min_step && unexplored && !min_unexplored)
+            }
+            None => step < 0xFFFE,
+        };
+        if better {
+            best = Some((step, unexplored, d));
         }
     }
-    Some(direction_to_go)
+    best.map(|(_, _, d)| d)
+}
+
+// True if the shortest route from `start` to `goal` is known: taking the unexplored walls as
+// absent does not find a shorter route than the walls known to be absent. False if the known
+// walls do not lead to the goal.
+pub fn is_shortest_proven<const W: usize, const H: usize>(
+    maze: &impl MazeStorage<W, H>,
+    goal: &Goal,
+    start: Position,
+) -> bool {
+    let mut stepmap = StepMap::<W, H>::new();
+    stepmap.calc_step_map(maze, StepMapMode::UnexploredAsPresent, goal);
+    let known = *stepmap.get(start.row, start.col);
+    stepmap.calc_step_map(maze, StepMapMode::UnexploredAsAbsent, goal);
+    known != 0xFFFE && known == *stepmap.get(start.row, start.col)
 }
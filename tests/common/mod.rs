@@ -0,0 +1,34 @@
+// Helpers shared by the integration tests. Each test crate uses some of them only.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use mm_maze_solver::maze::text::parse;
+use mm_maze_solver::maze::{Maze, MazeConfig};
+
+fn assets_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("bin/assets")
+}
+
+// The text mazes in bin/assets, all of them classic 16x16 ones
+pub fn assets() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(assets_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("txt"))
+        .collect();
+    assert!(!paths.is_empty());
+    paths.sort();
+    paths
+}
+
+pub fn read(path: &Path) -> (Maze, MazeConfig) {
+    parse(&fs::read_to_string(path).unwrap())
+        .unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+// The maze of bin/assets named `name`
+pub fn asset(name: &str) -> (Maze, MazeConfig) {
+    read(&assets_dir().join(name))
+}
@@ -0,0 +1,99 @@
+use mm_maze_solver::cost_map::{CostMap, CostParams};
+use mm_maze_solver::maze::text::parse;
+use mm_maze_solver::maze::Direction::{East, North, South, West};
+use mm_maze_solver::maze::{Direction, Goal, Maze, MazeConfig, Position, Wall};
+use mm_maze_solver::solver::{StepMap, StepMapMode};
+
+// From (0, 0) facing east to the goal, by the staircase in 4 moves and 3 turns, or by the
+// detour along the edge in 8 moves and 2 turns.
+const TWO_ROUTES: &str = "\
++-+-+-+-+-+
+|S        |
++-+ +-+-+ +
+| |   | | |
++-+-+ +-+ +
+| | |G    |
++-+-+-+-+-+
+";
+
+fn two_routes() -> Maze<5, 3> {
+    let (maze, _): (Maze<5, 3>, MazeConfig) = parse(TWO_ROUTES).unwrap();
+    maze
+}
+
+const START: Position = Position::new(0, 0);
+const STAIRCASE: [Direction; 4] = [East, South, East, South];
+const DETOUR: [Direction; 8] = [East, East, East, East, South, South, West, West];
+
+fn cost_route(maze: &Maze<5, 3>, params: &CostParams) -> Option<Vec<Direction>> {
+    let goal = Goal::cell(Position::new(2, 2));
+    let mut cost_map: CostMap<5, 3> = CostMap::new();
+    cost_map.calc_cost_map(maze, params, &goal);
+    let route = cost_map.route(maze, params, &goal, START, East)?;
+    Some(route.moves().to_vec())
+}
+
+fn step_route(maze: &Maze<5, 3>, mode: StepMapMode) -> Vec<Direction> {
+    let goal = Goal::cell(Position::new(2, 2));
+    let mut stepmap: StepMap<5, 3> = StepMap::new();
+    stepmap.calc_step_map(maze, mode, &goal);
+    let route = stepmap.route(maze, mode, &goal, START, East).unwrap();
+    route.moves().to_vec()
+}
+
+#[test]
+fn turn_penalty_prefers_straights() {
+    let maze = two_routes();
+    assert_eq!(
+        step_route(&maze, StepMapMode::UnexploredAsPresent),
+        STAIRCASE
+    );
+
+    // 40 + 3 * 20 is less than 80 + 2 * 20
+    let params = CostParams::default();
+    assert_eq!(cost_route(&maze, &params).unwrap(), STAIRCASE);
+
+    // 40 + 3 * 60 is more than 80 + 2 * 60
+    let params = CostParams {
+        straight: 10,
+        turn90: 60,
+        uturn: 120,
+    };
+    assert_eq!(cost_route(&maze, &params).unwrap(), DETOUR);
+}
+
+#[test]
+fn unexplored_walls_are_not_passed() {
+    let mut maze = two_routes();
+    maze.set_wall(1, 1, East, Wall::Unexplored);
+
+    // The step map passes the unexplored wall only if it is taken as absent.
+    assert_eq!(
+        step_route(&maze, StepMapMode::UnexploredAsAbsent),
+        STAIRCASE
+    );
+    assert_eq!(step_route(&maze, StepMapMode::UnexploredAsPresent), DETOUR);
+    // The cost map takes it as present.
+    assert_eq!(cost_route(&maze, &CostParams::default()).unwrap(), DETOUR);
+
+    maze.set_wall(0, 3, East, Wall::Unexplored);
+    assert_eq!(cost_route(&maze, &CostParams::default()), None);
+    assert_eq!(
+        step_route(&maze, StepMapMode::UnexploredAsAbsent),
+        STAIRCASE
+    );
+}
+
+#[test]
+fn cost_counts_moves_and_turns() {
+    let maze = two_routes();
+    let params = CostParams::default();
+    let goal = Goal::cell(Position::new(2, 2));
+    let mut cost_map: CostMap<5, 3> = CostMap::new();
+    cost_map.calc_cost_map(&maze, &params, &goal);
+
+    assert_eq!(cost_map.cost(2, 2, North), 0);
+    // The staircase facing east, and turning back first facing west
+    assert_eq!(cost_map.cost(0, 0, East), 4 * 10 + 3 * 20);
+    assert_eq!(cost_map.cost(0, 0, West), 4 * 10 + 3 * 20 + 60);
+}
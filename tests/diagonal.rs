@@ -0,0 +1,131 @@
+use mm_maze_solver::diagonal::{
+    DiagonalCostMap, DiagonalCostParams, DiagonalPath, Heading, WallNode,
+};
+use mm_maze_solver::maze::{Direction, Goal, Maze, Position, Wall, NESW};
+use mm_maze_solver::route::Route;
+
+// 5x5 maze walled everywhere but a staircase from (0, 0) down to the goal at (4, 4)
+fn staircase() -> Maze<5, 5> {
+    let mut maze = Maze::new();
+    for row in 0..5 {
+        for col in 0..5 {
+            for direction in NESW {
+                maze.set_wall(row, col, direction, Wall::Present);
+            }
+        }
+    }
+    for i in 0..4 {
+        maze.set_wall(i, i, Direction::East, Wall::Absent);
+        maze.set_wall(i, i + 1, Direction::South, Wall::Absent);
+    }
+    maze
+}
+
+fn staircase_route() -> Route<5, 5> {
+    let mut route = Route::new(Position::new(0, 0), Direction::East);
+    for _ in 0..4 {
+        route.push(Direction::East);
+        route.push(Direction::South);
+    }
+    route
+}
+
+// The two cells on both sides of the node
+fn cells_of(node: &WallNode) -> [Position; 2] {
+    match node.side {
+        Direction::North => [
+            Position::new(node.row - 1, node.col),
+            Position::new(node.row, node.col),
+        ],
+        _ => [
+            Position::new(node.row, node.col - 1),
+            Position::new(node.row, node.col),
+        ],
+    }
+}
+
+// Every move of the path crosses a cell from the node before it to the node after it,
+// through walls which are absent.
+fn assert_contiguous(path: &DiagonalPath<5, 5>, maze: &Maze<5, 5>, goal: &Goal) {
+    let nodes: Vec<(WallNode, Heading)> = path.nodes().collect();
+    assert_eq!(nodes.len(), path.len());
+    assert!(cells_of(&nodes[0].0).contains(&path.start()));
+    for (node, _) in &nodes {
+        assert_eq!(maze.get(node.row, node.col).get(node.side), Wall::Absent);
+    }
+    for pair in nodes.windows(2) {
+        let (cell, _) = pair[0].0.cell_ahead(pair[1].1).unwrap();
+        assert!(cells_of(&pair[0].0).contains(&cell));
+        assert!(cells_of(&pair[1].0).contains(&cell));
+    }
+    let (last, heading) = nodes[nodes.len() - 1];
+    let (cell, _) = last.cell_ahead(heading).unwrap();
+    assert!(goal.contains(cell.row, cell.col));
+}
+
+#[test]
+fn staircase_route_becomes_diagonal() {
+    let path = DiagonalPath::from_route(&staircase_route()).unwrap();
+    let mut expected = vec![Heading::East];
+    expected.extend([Heading::SouthEast; 7]);
+    assert_eq!(path.moves(), expected.as_slice());
+    assert_contiguous(&path, &staircase(), &Goal::cell(Position::new(4, 4)));
+
+    // Turning back cannot be a diagonal move
+    let mut route = staircase_route();
+    route.push(Direction::North);
+    assert!(DiagonalPath::from_route(&route).is_none());
+}
+
+#[test]
+fn staircase_is_planned_as_diagonal_run() {
+    let maze = staircase();
+    let goal = Goal::cell(Position::new(4, 4));
+    let params = DiagonalCostParams::default();
+    let mut cost_map: DiagonalCostMap<5, 5> = DiagonalCostMap::new();
+    cost_map.calc_cost_map(&maze, &params, &goal);
+
+    let path = cost_map
+        .path(&maze, &params, Position::new(0, 0), Direction::East)
+        .unwrap();
+    assert_eq!(path.moves()[0], Heading::East);
+    assert!(path.moves()[1..].iter().all(|&h| h == Heading::SouthEast));
+    assert_eq!(path.len(), 8);
+    assert_contiguous(&path, &maze, &goal);
+}
+
+#[test]
+fn open_maze_path_is_contiguous() {
+    let maze: Maze<5, 5> = Maze::new();
+    let goal = Goal::rect(Position::new(3, 3), 2, 2).unwrap();
+    let params = DiagonalCostParams::default();
+    let mut cost_map: DiagonalCostMap<5, 5> = DiagonalCostMap::new();
+    cost_map.calc_cost_map(&maze, &params, &goal);
+
+    let path = cost_map
+        .path(&maze, &params, Position::new(0, 0), Direction::East)
+        .unwrap();
+    assert!(path.moves().iter().any(|h| h.is_diagonal()));
+    assert_contiguous(&path, &maze, &goal);
+}
+
+#[test]
+fn unreachable_goal_has_no_path() {
+    let mut maze = staircase();
+    maze.set_wall(3, 4, Direction::South, Wall::Present);
+    let goal = Goal::cell(Position::new(4, 4));
+    let params = DiagonalCostParams::default();
+    let mut cost_map: DiagonalCostMap<5, 5> = DiagonalCostMap::new();
+    cost_map.calc_cost_map(&maze, &params, &goal);
+
+    assert!(cost_map
+        .path(&maze, &params, Position::new(0, 0), Direction::East)
+        .is_none());
+
+    // Unexplored walls are not passed either
+    maze.set_wall(3, 4, Direction::South, Wall::Unexplored);
+    cost_map.calc_cost_map(&maze, &params, &goal);
+    assert!(cost_map
+        .path(&maze, &params, Position::new(0, 0), Direction::East)
+        .is_none());
+}
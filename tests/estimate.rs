@@ -0,0 +1,105 @@
+use mm_maze_solver::estimate::{estimate_route, fastest_route, RunParams};
+use mm_maze_solver::maze::text::parse;
+use mm_maze_solver::maze::Direction::{self, East, South};
+use mm_maze_solver::maze::{Goal, Maze, MazeConfig, Position};
+use mm_maze_solver::motion::TurnSet;
+use mm_maze_solver::route::Route;
+use mm_maze_solver::solver::{StepMap, StepMapMode};
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-4
+}
+
+#[test]
+fn long_run_reaches_top_speed() {
+    let params = RunParams::default();
+    let (a, v) = (params.acceleration, params.max_speed);
+    let length = 3.0;
+    // Accelerate to v, cruise, decelerate to a stop
+    let ramp = v * v / (2.0 * a);
+    let expected = 2.0 * v / a + (length - 2.0 * ramp) / v;
+    assert!(close(params.straight_time(length, 0.0, 0.0), expected));
+
+    // From and to the speed of a turn
+    let turn = params.large_turn90.speed;
+    let ramp = (v * v - turn * turn) / (2.0 * a);
+    let expected = 2.0 * (v - turn) / a + (length - 2.0 * ramp) / v;
+    assert!(close(params.straight_time(length, turn, turn), expected));
+}
+
+#[test]
+fn short_run_never_reaches_top_speed() {
+    let params = RunParams::default();
+    let a = params.acceleration;
+    // The run is too short to reach top speed before decelerating, so it is a triangle
+    for halves in 1..=8 {
+        let length = halves as f32 * params.cell_length / 2.0;
+        let peak = (a * length).sqrt();
+        assert!(peak < params.max_speed);
+        assert!(close(
+            params.straight_time(length, 0.0, 0.0),
+            2.0 * peak / a
+        ));
+        assert!(params.straight_time(length, 0.0, 0.0) > length / peak);
+    }
+}
+
+// Two shortest routes around the closed center, east then south or south then east.
+const AROUND: &str = "\
++-+-+-+
+|S    |
++ +-+ +
+| | | |
++ +-+ +
+|    G|
++-+-+-+
+";
+
+fn route(moves: &[Direction]) -> Route<3, 3> {
+    let mut route = Route::new(Position::new(0, 0), East);
+    for &d in moves {
+        route.push(d);
+    }
+    route
+}
+
+#[test]
+fn fastest_of_equally_short_routes() {
+    let (maze, config): (Maze<3, 3>, MazeConfig) = parse(AROUND).unwrap();
+    let goal = Goal::cell(Position::new(2, 2));
+    assert_eq!(config.goal, goal);
+    let params = RunParams::default();
+
+    let mut stepmap: StepMap<3, 3> = StepMap::new();
+    stepmap.calc_step_map(&maze, StepMapMode::UnexploredAsPresent, &goal);
+    let mut candidates = Vec::new();
+    stepmap.for_each_route(
+        &maze,
+        StepMapMode::UnexploredAsPresent,
+        Position::new(0, 0),
+        East,
+        8,
+        |route| candidates.push(route.moves().to_vec()),
+    );
+    assert_eq!(candidates.len(), 2);
+
+    for turn_set in [TurnSet::Search, TurnSet::Fast] {
+        let (fastest, time) = fastest_route(
+            &maze,
+            &goal,
+            Position::new(0, 0),
+            East,
+            turn_set,
+            &params,
+            8,
+        )
+        .unwrap();
+        // Heading south first needs a turn in place
+        assert_eq!(fastest.moves(), [East, East, South, South]);
+        let pivot = estimate_route(&route(&[South, South, East, East]), turn_set, &params);
+        assert!(close(params.pivot90 + time, pivot.unwrap()));
+        for moves in &candidates {
+            assert!(estimate_route(&route(moves), turn_set, &params).unwrap() >= time);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use mm_maze_solver::maze::maz::{self, LengthError};
+use mm_maze_solver::maze::{Maze, MAZE_SIZE};
+
+// Converts every maze in bin/assets to .maz and back.
+#[test]
+fn maz_format_round_trips() {
+    for path in common::assets() {
+        let (maze, _) = common::read(&path);
+
+        let mut bytes = [0; MAZE_SIZE * MAZE_SIZE];
+        maz::write(&maze, &mut bytes).unwrap();
+        // The start cell is open to the north of the format only.
+        assert_eq!(bytes[0], 0x0E, "{}", path.display());
+
+        let parsed: Maze = maz::parse(&bytes).unwrap();
+        assert!(parsed.grid == maze.grid, "{}: walls differ", path.display());
+    }
+}
+
+#[test]
+fn maz_length_is_checked() {
+    let result: Result<Maze, _> = maz::parse(&[0; 255]);
+    assert_eq!(
+        result.err(),
+        Some(LengthError {
+            expected: 256,
+            found: 255
+        })
+    );
+}
@@ -0,0 +1,118 @@
+use mm_maze_solver::maze::text::parse;
+use mm_maze_solver::maze::{
+    Direction, Goal, Maze, MazeConfig, Position, Start, Wall, GOAL_CAPACITY,
+};
+use mm_maze_solver::solver::{StepMap, StepMapMode};
+
+// Mazes wider than they are high: 5 columns and 3 rows
+
+#[test]
+fn walls_of_non_square_maze() {
+    let mut maze: Maze<5, 3> = Maze::new();
+    // Outer walls on every side
+    for col in 0..5 {
+        assert_eq!(maze.get(0, col).get(Direction::North), Wall::Present);
+        assert_eq!(maze.get(2, col).get(Direction::South), Wall::Present);
+    }
+    for row in 0..3 {
+        assert_eq!(maze.get(row, 0).get(Direction::West), Wall::Present);
+        assert_eq!(maze.get(row, 4).get(Direction::East), Wall::Present);
+    }
+
+    // A wall is shared by the cells on both sides
+    maze.set_wall(1, 3, Direction::East, Wall::Present);
+    assert_eq!(maze.get(1, 4).get(Direction::West), Wall::Present);
+    maze.set_wall(2, 4, Direction::North, Wall::Unexplored);
+    assert_eq!(maze.get(1, 4).get(Direction::South), Wall::Unexplored);
+    assert_eq!(maze.get(1, 3).get(Direction::South), Wall::Absent);
+}
+
+#[test]
+fn step_map_of_non_square_maze() {
+    let mut maze: Maze<5, 3> = Maze::new();
+    // The goal is in the last column, which would be out of bounds as a row
+    let goal = Goal::cell(Position::new(0, 4));
+    let mut stepmap: StepMap<5, 3> = StepMap::new();
+    stepmap.calc_step_map(&maze, StepMapMode::UnexploredAsAbsent, &goal);
+    for row in 0..3 {
+        for col in 0..5 {
+            assert_eq!(
+                *stepmap.get(row, col) as usize,
+                row + 4 - col,
+                "({}, {})",
+                row,
+                col
+            );
+        }
+    }
+
+    // Around an unexplored wall unless it is taken as absent
+    maze.set_wall(0, 3, Direction::East, Wall::Unexplored);
+    stepmap.calc_step_map(&maze, StepMapMode::UnexploredAsPresent, &goal);
+    assert_eq!(*stepmap.get(0, 3), 3);
+    stepmap.calc_step_map(&maze, StepMapMode::UnexploredAsAbsent, &goal);
+    assert_eq!(*stepmap.get(0, 3), 1);
+}
+
+#[test]
+fn lines_of_non_square_maze() {
+    let mut maze: Maze<5, 3> = Maze::new();
+    maze.set_wall(1, 1, Direction::East, Wall::Present);
+    maze.set_wall(0, 3, Direction::South, Wall::Unexplored);
+    let config = MazeConfig::new(Start::default(), Goal::cell(Position::new(2, 4)));
+
+    let lines: Vec<String> = maze.lines_iter(&config).map(|l| l.to_string()).collect();
+    // Pillars and walls around 3 rows, cells 3 characters wide in 5 columns
+    assert_eq!(lines.len(), 7);
+    assert!(lines.iter().all(|l| l.chars().count() == 21));
+    assert!(lines[5].ends_with(" G |"), "{}", lines[5]);
+
+    let text: String = lines.iter().map(|l| format!("{}\n", l)).collect();
+    let (parsed, parsed_config): (Maze<5, 3>, MazeConfig) = parse(&text).unwrap();
+    assert!(parsed.grid == maze.grid);
+    assert_eq!(parsed_config, config);
+}
+
+#[test]
+#[should_panic(expected = "start heading into the outer wall")]
+fn start_facing_outer_wall_is_rejected() {
+    let start = Start {
+        position: Position::new(2, 0),
+        heading: Direction::South,
+    };
+    let _: Maze<5, 3> = Maze::with_start(&start);
+}
+
+#[test]
+#[should_panic(expected = "start outside of the maze")]
+fn start_outside_of_maze_is_rejected() {
+    let start = Start {
+        position: Position::new(3, 0),
+        heading: Direction::North,
+    };
+    let _: Maze<5, 3> = Maze::with_start(&start);
+}
+
+#[test]
+fn goal_holds_at_most_its_capacity() {
+    let full = Goal::rect(Position::new(0, 0), 4, 4).unwrap();
+    assert_eq!(full.len(), GOAL_CAPACITY);
+    assert!(Goal::rect(Position::new(0, 0), 3, 6).is_none());
+
+    let row: Vec<Position> = (0..=GOAL_CAPACITY)
+        .map(|col| Position::new(0, col))
+        .collect();
+    assert!(Goal::from_cells(&row).is_none());
+    let goal = Goal::from_cells(&row[..GOAL_CAPACITY]).unwrap();
+    assert_eq!(goal.cells(), &row[..GOAL_CAPACITY]);
+    // Cells given twice are counted once
+    let twice: Vec<Position> = row[..GOAL_CAPACITY]
+        .iter()
+        .chain(&row[..4])
+        .copied()
+        .collect();
+    assert_eq!(Goal::from_cells(&twice), Some(goal));
+
+    assert_eq!(Goal::center::<5, 3>().cells(), &[Position::new(1, 2)]);
+    assert_eq!(Goal::center::<16, 16>().len(), 4);
+}
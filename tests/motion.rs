@@ -0,0 +1,102 @@
+use mm_maze_solver::diagonal::DiagonalPath;
+use mm_maze_solver::maze::Direction::{self, East, South};
+use mm_maze_solver::maze::Position;
+use mm_maze_solver::motion::{compile, Motion, MotionList, Side, TurnSet};
+use mm_maze_solver::route::Route;
+
+// Motions from the start (0, 0) heading east to the end of the moves
+fn motions(moves: &[Direction], turn_set: TurnSet) -> Vec<Motion> {
+    let mut route: Route<5, 5> = Route::new(Position::new(0, 0), East);
+    for &d in moves {
+        route.push(d);
+    }
+    let path = DiagonalPath::from_route(&route).unwrap();
+    compile(&path, turn_set).unwrap().as_slice().to_vec()
+}
+
+#[test]
+fn straight_runs_are_merged() {
+    // Leaving the start and entering the goal are half cells
+    for turn_set in [TurnSet::Search, TurnSet::Fast] {
+        assert_eq!(
+            motions(&[East, East, East], turn_set),
+            [Motion::Straight(6)]
+        );
+    }
+
+    let mut list: MotionList<5, 5> = MotionList::new();
+    assert!(list.push(Motion::Straight(2)));
+    assert!(list.push(Motion::Straight(0)));
+    assert!(list.push(Motion::Straight(3)));
+    assert_eq!(list.as_slice(), [Motion::Straight(5)]);
+}
+
+#[test]
+fn turns_of_90_degrees() {
+    let moves = [East, East, South, South];
+    assert_eq!(
+        motions(&moves, TurnSet::Search),
+        [
+            Motion::Straight(3),
+            Motion::SmallTurn90(Side::Right),
+            Motion::Straight(3)
+        ]
+    );
+    // Large turns start and end in the centers of the cells around the corner
+    assert_eq!(
+        motions(&moves, TurnSet::Fast),
+        [
+            Motion::Straight(2),
+            Motion::LargeTurn90(Side::Right),
+            Motion::Straight(2)
+        ]
+    );
+}
+
+#[test]
+fn staircase_is_run_diagonally() {
+    let moves = [East, East, South, East, South, South];
+    assert_eq!(
+        motions(&moves, TurnSet::Search),
+        [
+            Motion::Straight(3),
+            Motion::SmallTurn90(Side::Right),
+            Motion::SmallTurn90(Side::Left),
+            Motion::SmallTurn90(Side::Right),
+            Motion::Straight(3)
+        ]
+    );
+    assert_eq!(
+        motions(&moves, TurnSet::Fast),
+        [
+            Motion::Straight(2),
+            Motion::DiagonalIn45(Side::Right),
+            Motion::Diagonal(1),
+            Motion::DiagonalOut45(Side::Right),
+            Motion::Straight(2)
+        ]
+    );
+
+    // Diagonal from the start to the goal
+    assert_eq!(
+        motions(&[East, South, East, South, East, South], TurnSet::Fast),
+        [
+            Motion::DiagonalIn45(Side::Right),
+            Motion::Diagonal(3),
+            Motion::DiagonalOut45(Side::Right)
+        ]
+    );
+}
+
+#[test]
+fn full_list_refuses_motions() {
+    let mut list: MotionList<1, 1> = MotionList::new();
+    for _ in 0..2 {
+        assert!(list.push(Motion::Pivot180));
+        assert!(list.push(Motion::Straight(1)));
+    }
+    assert!(!list.push(Motion::Pivot180));
+    // Merging needs no room
+    assert!(list.push(Motion::Straight(1)));
+    assert_eq!(list.len(), 4);
+}
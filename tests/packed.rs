@@ -0,0 +1,129 @@
+mod common;
+
+use std::mem::size_of;
+
+use mm_maze_solver::maze::packed::{packed_len, PackedMaze};
+use mm_maze_solver::maze::{
+    nsew_to_index, Direction, Maze, MazeStorage, Position, Start, Wall, TOZAINANBOKU,
+};
+use mm_maze_solver::solver::{decide_direction, StepMap, StepMapMode, TieBreak};
+
+#[test]
+fn packed_maze_is_smaller() {
+    // Two bits for each of the 480 walls inside the classic maze
+    assert_eq!(size_of::<PackedMaze>(), 120);
+    assert_eq!(size_of::<PackedMaze<32, 32, { packed_len(32, 32) }>>(), 496);
+    assert_eq!(packed_len(5, 3), 6);
+    assert!(size_of::<PackedMaze>() < size_of::<Maze>());
+}
+
+// Conversions keep every wall of half-size and non-square mazes.
+#[test]
+fn conversion_round_trips() {
+    let mut half: Maze<32, 32> = Maze::new();
+    for i in 0..31 {
+        half.set_wall(i, i, Direction::East, Wall::Present);
+        half.set_wall(i, 31 - i, Direction::South, Wall::Unexplored);
+        half.set_wall(31, i, Direction::East, Wall::Unexplored);
+    }
+    let packed: PackedMaze<32, 32, { packed_len(32, 32) }> = PackedMaze::from(&half);
+    assert!(Maze::from(&packed).grid == half.grid);
+    assert_eq!(packed.get_wall(31, 30, Direction::East), Wall::Unexplored);
+    assert_eq!(packed.get_wall(31, 31, Direction::East), Wall::Present);
+
+    let mut wide: Maze<5, 3> = Maze::new();
+    wide.set_wall(2, 3, Direction::North, Wall::Present);
+    wide.set_wall(1, 4, Direction::West, Wall::Unexplored);
+    let packed: PackedMaze<5, 3, 6> = PackedMaze::from(&wide);
+    assert!(Maze::from(&packed).grid == wide.grid);
+    assert_eq!(packed.get_wall(1, 3, Direction::South), Wall::Present);
+    assert_eq!(packed.get_wall(1, 3, Direction::East), Wall::Unexplored);
+}
+
+// Searches every maze with both storages and compares the walls and step maps on every step.
+#[test]
+fn packed_maze_matches_cell_storage() {
+    for path in common::assets() {
+        let (actual, config) = common::read(&path);
+        let packed: PackedMaze = PackedMaze::from(&actual);
+        assert!(
+            Maze::from(&packed).grid == actual.grid,
+            "{}",
+            path.display()
+        );
+
+        let mut cells: Maze = Maze::with_start(&config.start);
+        let mut bits: PackedMaze = PackedMaze::with_start(&config.start);
+        for (row, col) in (0..16).flat_map(|row| (0..16).map(move |col| (row, col))) {
+            for d in TOZAINANBOKU {
+                if cells.get(row, col).get(d) == Wall::Absent {
+                    cells.set_wall(row, col, d, Wall::Unexplored);
+                    bits.set_wall(row, col, d, Wall::Unexplored);
+                }
+            }
+        }
+
+        let mut cells_map: StepMap = StepMap::new();
+        let mut bits_map: StepMap = StepMap::new();
+        let (mut row, mut col) = (config.start.position.row, config.start.position.col);
+        let mut heading = config.start.heading;
+        let tie_break = TieBreak::default();
+        while !config.goal.contains(row, col) {
+            for d in TOZAINANBOKU {
+                let wall = actual.get(row, col).get(d);
+                cells.set_wall(row, col, d, wall);
+                bits.set_wall(row, col, d, wall);
+            }
+            let cells_dir = decide_direction(
+                &cells,
+                &config.goal,
+                row,
+                col,
+                heading,
+                &tie_break,
+                &mut cells_map,
+            );
+            let bits_dir = decide_direction(
+                &bits,
+                &config.goal,
+                row,
+                col,
+                heading,
+                &tie_break,
+                &mut bits_map,
+            );
+            assert_eq!(cells_dir, bits_dir, "{}", path.display());
+            assert!(cells_map.grid == bits_map.grid, "{}", path.display());
+
+            bits_map.calc_step_map(&bits, StepMapMode::UnexploredAsPresent, &config.goal);
+            cells_map.calc_step_map(&cells, StepMapMode::UnexploredAsPresent, &config.goal);
+            assert!(cells_map.grid == bits_map.grid, "{}", path.display());
+
+            let next = cells_dir.unwrap();
+            heading = next;
+            let (d_col, d_row) = nsew_to_index(next);
+            row = (row as isize + d_row) as usize;
+            col = (col as isize + d_col) as usize;
+        }
+        for row in 0..16 {
+            for col in 0..16 {
+                assert_eq!(
+                    bits.get_cell(row, col),
+                    *cells.get_cell(row, col),
+                    "{}",
+                    path.display()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "start heading into the outer wall")]
+fn start_facing_outer_wall_is_rejected() {
+    let start = Start {
+        position: Position::new(0, 4),
+        heading: Direction::East,
+    };
+    let _: PackedMaze<5, 3, 6> = PackedMaze::with_start(&start);
+}
@@ -0,0 +1,96 @@
+use mm_maze_solver::cost_map::{CostMap, CostParams};
+use mm_maze_solver::maze::text::parse;
+use mm_maze_solver::maze::Direction::{self, East, North, South};
+use mm_maze_solver::maze::{Maze, MazeConfig, Position, Wall};
+use mm_maze_solver::route::Route;
+use mm_maze_solver::solver::{StepMap, StepMapMode};
+
+// From the start facing south, around the dead end in the middle column and up to the goal.
+const U_TURN: &str = "\
++-+-+-+
+|S| |G|
++ + + +
+| | | |
++ +-+ +
+|     |
++-+-+-+
+";
+
+// Cells and headings of the only route through U_TURN
+const EXPECTED: [((usize, usize), Direction); 7] = [
+    ((0, 0), South),
+    ((1, 0), South),
+    ((2, 0), South),
+    ((2, 1), East),
+    ((2, 2), East),
+    ((1, 2), North),
+    ((0, 2), North),
+];
+
+fn cells(route: &Route<3, 3>) -> Vec<((usize, usize), Direction)> {
+    route
+        .iter()
+        .map(|(p, heading)| ((p.row, p.col), heading))
+        .collect()
+}
+
+fn u_turn() -> (Maze<3, 3>, MazeConfig) {
+    let (maze, config) = parse(U_TURN).unwrap();
+    assert_eq!(config.start.heading, South);
+    (maze, config)
+}
+
+fn step_route(maze: &Maze<3, 3>, config: &MazeConfig) -> Option<Route<3, 3>> {
+    let mode = StepMapMode::UnexploredAsPresent;
+    let mut stepmap: StepMap<3, 3> = StepMap::new();
+    stepmap.calc_step_map(maze, mode, &config.goal);
+    let start = config.start;
+    stepmap.route(maze, mode, &config.goal, start.position, start.heading)
+}
+
+fn cost_route(maze: &Maze<3, 3>, config: &MazeConfig) -> Option<Route<3, 3>> {
+    let params = CostParams::default();
+    let mut cost_map: CostMap<3, 3> = CostMap::new();
+    cost_map.calc_cost_map(maze, &params, &config.goal);
+    let start = config.start;
+    cost_map.route(maze, &params, &config.goal, start.position, start.heading)
+}
+
+#[test]
+fn follow_stops_in_the_goal() {
+    let (_, config) = u_turn();
+    let mut moves = EXPECTED[1..].iter().map(|&(_, d)| d);
+    let route = Route::<3, 3>::follow(Position::new(0, 0), South, &config.goal, |_, _| {
+        moves.next()
+    })
+    .unwrap();
+    assert_eq!(cells(&route), EXPECTED);
+    assert_eq!(route.end(), Position::new(0, 2));
+
+    // Giving up, or going round in circles until the route is full
+    let gives_up = Route::<3, 3>::follow(Position::new(0, 0), South, &config.goal, |_, _| None);
+    assert!(gives_up.is_none());
+    let mut turns = [South, North].into_iter().cycle();
+    let circles = Route::<3, 3>::follow(Position::new(0, 0), South, &config.goal, |_, _| {
+        turns.next()
+    });
+    assert!(circles.is_none());
+}
+
+#[test]
+fn step_map_route_from_start_to_goal() {
+    let (mut maze, config) = u_turn();
+    assert_eq!(cells(&step_route(&maze, &config).unwrap()), EXPECTED);
+
+    maze.set_wall(1, 2, North, Wall::Present);
+    assert!(step_route(&maze, &config).is_none());
+}
+
+#[test]
+fn cost_map_route_from_start_to_goal() {
+    let (mut maze, config) = u_turn();
+    assert_eq!(cells(&cost_route(&maze, &config).unwrap()), EXPECTED);
+
+    maze.set_wall(1, 2, North, Wall::Present);
+    assert!(cost_route(&maze, &config).is_none());
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use mm_maze_solver::maze::{Direction, Goal, Maze, MazeConfig, Position, Start, Wall};
+use mm_maze_solver::rules::{check, Category, Hand, Violation};
+
+fn asset(name: &str) -> (Maze, MazeConfig) {
+    let (maze, mut config) = common::asset(name);
+    // The assets mark one cell of the goal only.
+    config.goal = Goal::center::<16, 16>();
+    (maze, config)
+}
+
+#[test]
+fn contest_maze_follows_the_rules() {
+    let (maze, config) = asset("kansai2012classic.txt");
+    assert_eq!(check(&maze, &config, Category::Classic), vec![]);
+}
+
+#[test]
+fn wall_follower_is_detected() {
+    let (maze, config) = asset("AllJapan_028_2007_classic_exp_fin_16x16.txt");
+    assert_eq!(
+        check(&maze, &config, Category::Classic),
+        vec![
+            Violation::WallFollowerReachesGoal { hand: Hand::Left },
+            Violation::WallFollowerReachesGoal { hand: Hand::Right },
+        ]
+    );
+}
+
+#[test]
+fn start_and_goal_are_checked() {
+    let (mut maze, mut config) = asset("kansai2012classic.txt");
+    maze.set_wall(0, 0, Direction::South, Wall::Absent);
+    assert_eq!(
+        check(&maze, &config, Category::Classic),
+        vec![Violation::StartNotWalled {
+            direction: Direction::South
+        }]
+    );
+
+    config.goal = Goal::cell(Position::new(7, 7));
+    assert_eq!(
+        check(&maze, &config, Category::Classic)[1..],
+        [Violation::GoalNotCentered]
+    );
+
+    config.start = Start {
+        position: Position::new(1, 0),
+        heading: Direction::East,
+    };
+    assert_eq!(
+        check(&maze, &config, Category::Classic),
+        vec![Violation::StartNotInCorner]
+    );
+    assert_eq!(
+        check(&maze, &config, Category::HalfSize),
+        vec![Violation::WrongSize {
+            width: 16,
+            height: 16
+        }]
+    );
+}
+
+#[test]
+fn only_goal_pillars_may_stand_alone() {
+    let maze: Maze = Maze::new();
+    let config = MazeConfig::classic::<16, 16>();
+    let lonely = check(&maze, &config, Category::Classic)
+        .into_iter()
+        .filter(|v| matches!(v, Violation::LonelyPillar { .. }))
+        .count();
+    // All 15x15 interior pillars but the goal centre and the one at the start wall
+    assert_eq!(lonely, 15 * 15 - 2);
+}
+
+#[test]
+fn goal_outside_of_half_size_maze_is_reported() {
+    let maze: Maze<32, 32> = Maze::new();
+    let config = MazeConfig::new(Start::default(), Goal::cell(Position::new(40, 40)));
+    assert_eq!(
+        check(&maze, &config, Category::HalfSize),
+        vec![Violation::GoalOutOfBounds { row: 40, col: 40 }]
+    );
+}
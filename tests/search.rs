@@ -0,0 +1,95 @@
+mod common;
+
+use mm_maze_solver::maze::{Direction, Goal, Maze, MazeConfig, Position, Start, Wall};
+use mm_maze_solver::rules::{follow_wall, Hand};
+use mm_maze_solver::search::{FullExploration, ShortestCandidates, WallFollower};
+use mm_maze_solver::sim::{Simulator, Step};
+use mm_maze_solver::solver::{StepMap, StepMapMode};
+
+#[test]
+fn wall_follower_matches_rules() {
+    for path in common::assets() {
+        for hand in [Hand::Left, Hand::Right] {
+            let (actual, config) = common::read(&path);
+            let expected = follow_wall(&actual, &config, hand);
+            let mut simulator = Simulator::with_strategy(actual, config, WallFollower::new(hand));
+            let result = simulator.run_to_goal();
+            assert_eq!(result.reached_goal, expected, "{}", path.display());
+            let Position { row, col } = simulator.position();
+            assert_eq!(config.goal.contains(row, col), expected);
+        }
+    }
+}
+
+#[test]
+fn full_exploration_knows_every_reachable_wall() {
+    for path in common::assets() {
+        let (actual, config) = common::read(&path);
+        let mut reachable: StepMap = StepMap::new();
+        reachable.calc_step_map(
+            &actual,
+            StepMapMode::UnexploredAsPresent,
+            &Goal::cell(config.start.position),
+        );
+        let mut simulator = Simulator::with_strategy(actual, config, FullExploration::new());
+        // Cells whose walls are known from their neighbors need not be visited.
+        simulator.run_to_goal();
+        assert!(!matches!(simulator.step(), Step::Moved(_)));
+
+        for row in 0..16 {
+            for col in 0..16 {
+                if *reachable.get(row, col) != 0xFFFE {
+                    assert_eq!(
+                        simulator.local().get(row, col),
+                        simulator.actual().get(row, col),
+                        "{}",
+                        path.display()
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn shortest_candidates_stop_when_proven() {
+    for path in common::assets() {
+        let (actual, config) = common::read(&path);
+        let strategy = ShortestCandidates::new(config.start);
+        let mut simulator = Simulator::with_strategy(actual, config, strategy);
+        simulator.run_to_goal();
+        assert!(simulator.is_shortest_proven(), "{}", path.display());
+    }
+}
+
+#[test]
+fn step_with_other_strategy_keeps_adachi_up_to_date() {
+    let path = &common::assets()[0];
+    let (actual, config) = common::read(path);
+    let mut simulator = Simulator::new(actual, config);
+    let mut left = WallFollower::new(Hand::Left);
+    for _ in 0..10 {
+        simulator.step_with(&mut left);
+    }
+    simulator.run_to_goal();
+
+    let mut expected: StepMap = StepMap::new();
+    expected.calc_step_map(
+        simulator.local(),
+        StepMapMode::UnexploredAsAbsent,
+        &config.goal,
+    );
+    assert!(simulator.step_map().grid == expected.grid);
+    assert!(simulator.result().reached_goal);
+}
+
+#[test]
+fn strategy_cannot_move_through_walls() {
+    // The wall follower only moves through known walls, so it stops in a closed start cell.
+    let mut actual: Maze<4, 4> = Maze::new();
+    actual.set_wall(0, 0, Direction::East, Wall::Present);
+    let config = MazeConfig::new(Start::default(), Goal::cell(Position::new(3, 3)));
+    let mut simulator = Simulator::with_strategy(actual, config, WallFollower::new(Hand::Right));
+    assert_eq!(simulator.step(), Step::Stopped);
+    assert_eq!(simulator.result().steps, 0);
+}
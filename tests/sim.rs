@@ -0,0 +1,177 @@
+mod common;
+
+use mm_maze_solver::maze::{
+    nsew_to_fblr, nsew_to_index, Direction, DirectionOfTravel, Goal, Maze, MazeConfig, Position,
+    Wall,
+};
+use mm_maze_solver::sim::{SimResult, Simulator, Step};
+use mm_maze_solver::solver::{is_shortest_proven, StepMap, StepMapMode};
+
+// Walls inside the maze which are not unexplored, each counted once
+fn known_walls<const W: usize, const H: usize>(maze: &Maze<W, H>, unknown: Wall) -> usize {
+    let mut count = 0;
+    for row in 0..H {
+        for col in 0..W {
+            if col + 1 < W && maze.get(row, col).east != unknown {
+                count += 1;
+            }
+            if row + 1 < H && maze.get(row, col).south != unknown {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+#[test]
+fn simulator_reaches_every_goal() {
+    for path in common::assets() {
+        let (actual, config) = common::read(&path);
+        let mut simulator = Simulator::new(actual, config);
+
+        let mut expected = SimResult {
+            cells_visited: 1,
+            ..SimResult::default()
+        };
+        let mut visited = vec![config.start.position];
+        let mut heading = config.start.heading;
+        loop {
+            let Position { row, col } = simulator.position();
+            let cell = *simulator.actual().get(row, col);
+            match simulator.step() {
+                Step::Moved(direction) => {
+                    assert_eq!(cell.get(direction), Wall::Absent, "{}", path.display());
+                    let (d_col, d_row) = nsew_to_index(direction);
+                    let next = Position::new(
+                        (row as isize + d_row) as usize,
+                        (col as isize + d_col) as usize,
+                    );
+                    assert_eq!(simulator.position(), next, "{}", path.display());
+                    assert_eq!(simulator.heading(), direction);
+
+                    expected.steps += 1;
+                    match nsew_to_fblr(heading, direction) {
+                        DirectionOfTravel::Forward => {}
+                        DirectionOfTravel::Backward => expected.u_turns += 1,
+                        _ => expected.turns += 1,
+                    }
+                    if !visited.contains(&next) {
+                        visited.push(next);
+                        expected.cells_visited += 1;
+                    }
+                    heading = direction;
+                }
+                Step::AtGoal => break,
+                Step::Stopped => panic!("{}: Cannot reach the goal", path.display()),
+            }
+        }
+
+        let result = simulator.result();
+        expected.reached_goal = true;
+        expected.walls_discovered = known_walls(simulator.local(), Wall::Unexplored)
+            - known_walls(&Maze::<16, 16>::with_start(&config.start), Wall::Absent);
+        assert_eq!(result, expected, "{}", path.display());
+        assert_eq!(simulator.step(), Step::AtGoal);
+    }
+}
+
+#[test]
+fn round_trip_returns_to_start() {
+    for path in common::assets() {
+        let (actual, config) = common::read(&path);
+        let mut shortest: StepMap = StepMap::new();
+        shortest.calc_step_map(&actual, StepMapMode::UnexploredAsPresent, &config.goal);
+        let start = config.start.position;
+        let mut simulator = Simulator::new(actual, config);
+
+        let trip = simulator.run_round_trip();
+        assert!(trip.outbound.reached_goal, "{}", path.display());
+        assert!(trip.returned, "{}", path.display());
+        assert_eq!(simulator.position(), start);
+        assert!(trip.total.steps > trip.outbound.steps);
+        assert!(trip.total.cells_visited >= trip.outbound.cells_visited);
+        assert_eq!(trip.total, simulator.result());
+
+        // The route through the known walls cannot be shorter than the actual shortest route,
+        // and is as long once it is confirmed.
+        let coverage = trip.path.unwrap();
+        let steps = *shortest.get(start.row, start.col) as usize;
+        assert!(coverage.confirmed <= coverage.cells);
+        assert!(coverage.cells <= steps + 1, "{}", path.display());
+        if coverage.confirmed == coverage.cells {
+            assert_eq!(coverage.cells, steps + 1, "{}", path.display());
+        }
+    }
+}
+
+#[test]
+fn exploration_proves_shortest_route() {
+    for path in common::assets() {
+        let (actual, config) = common::read(&path);
+        let start = config.start.position;
+        assert!(is_shortest_proven(&actual, &config.goal, start));
+        let mut shortest: StepMap = StepMap::new();
+        shortest.calc_step_map(&actual, StepMapMode::UnexploredAsPresent, &config.goal);
+        let mut simulator = Simulator::new(actual, config);
+
+        simulator.run_to_goal();
+        assert!(simulator.run_until_proven(), "{}", path.display());
+        assert!(simulator.is_shortest_proven());
+
+        let mut known: StepMap = StepMap::new();
+        known.calc_step_map(
+            simulator.local(),
+            StepMapMode::UnexploredAsPresent,
+            &config.goal,
+        );
+        assert_eq!(
+            known.get(start.row, start.col),
+            shortest.get(start.row, start.col),
+            "{}",
+            path.display()
+        );
+        let coverage = simulator.path_coverage().unwrap();
+        assert_eq!(
+            coverage.cells,
+            *shortest.get(start.row, start.col) as usize + 1
+        );
+
+        simulator.return_to_start();
+        assert_eq!(simulator.run_to_goal(), simulator.result());
+        assert_eq!(simulator.position(), start);
+    }
+}
+
+#[test]
+fn unexplored_walls_leave_shortest_route_unproven() {
+    let goal = Goal::cell(Position::new(0, 3));
+    let start = Position::new(0, 0);
+    let mut maze: Maze<4, 4> = Maze::new();
+    assert!(is_shortest_proven(&maze, &goal, start));
+
+    // The unexplored wall could open a shorter way than around it.
+    maze.set_wall(0, 1, Direction::East, Wall::Unexplored);
+    assert!(!is_shortest_proven(&maze, &goal, start));
+    maze.set_wall(0, 1, Direction::East, Wall::Present);
+    assert!(is_shortest_proven(&maze, &goal, start));
+
+    // Nothing is proven without a known way to the goal.
+    maze.set_wall(0, 3, Direction::West, Wall::Unexplored);
+    maze.set_wall(0, 3, Direction::South, Wall::Unexplored);
+    assert!(!is_shortest_proven(&maze, &goal, start));
+}
+
+#[test]
+fn walled_off_goal_is_unreachable() {
+    let mut actual: Maze<4, 4> = Maze::new();
+    for direction in [Direction::North, Direction::West] {
+        actual.set_wall(3, 3, direction, Wall::Present);
+    }
+    let config = MazeConfig::new(Default::default(), Goal::cell(Position::new(3, 3)));
+    let trip = Simulator::new(actual, config).run_round_trip();
+    assert!(!trip.outbound.reached_goal);
+    assert!(trip.outbound.steps > 0);
+    assert!(!trip.returned);
+    assert_eq!(trip.outbound, trip.total);
+    assert_eq!(trip.path, None);
+}
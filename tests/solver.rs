@@ -0,0 +1,223 @@
+mod common;
+
+use mm_maze_solver::maze::{Direction, DirectionOfTravel, Goal, Maze, Position, Wall};
+use mm_maze_solver::search::SearchStrategy;
+use mm_maze_solver::sim::{Simulator, Step};
+use mm_maze_solver::solver::{decide_direction, follow_step_map, StepMap, StepMapMode, TieBreak};
+
+// Open 4x4 maze with the goal at the south-east corner. From (1, 1), the cells east and south
+// are both one step closer.
+fn decide(maze: &Maze<4, 4>, heading: Direction, tie_break: &TieBreak) -> Option<Direction> {
+    let mut stepmap: StepMap<4, 4> = StepMap::new();
+    let goal = Goal::cell(Position::new(3, 3));
+    decide_direction(maze, &goal, 1, 1, heading, tie_break, &mut stepmap)
+}
+
+fn open_maze() -> Maze<4, 4> {
+    let mut maze = Maze::new();
+    maze.set_wall(0, 0, Direction::South, Wall::Absent);
+    maze
+}
+
+#[test]
+fn ties_prefer_going_straight() {
+    let maze = open_maze();
+    let tie_break = TieBreak::default();
+    assert_eq!(
+        decide(&maze, Direction::East, &tie_break),
+        Some(Direction::East)
+    );
+    assert_eq!(
+        decide(&maze, Direction::South, &tie_break),
+        Some(Direction::South)
+    );
+    // Turning right comes before turning back.
+    assert_eq!(
+        decide(&maze, Direction::North, &tie_break),
+        Some(Direction::East)
+    );
+    // Turning left comes before turning back.
+    assert_eq!(
+        decide(&maze, Direction::West, &tie_break),
+        Some(Direction::South)
+    );
+}
+
+#[test]
+fn tie_break_order_is_configurable() {
+    let maze = open_maze();
+    let tie_break = TieBreak {
+        order: [
+            DirectionOfTravel::Backward,
+            DirectionOfTravel::Left,
+            DirectionOfTravel::Right,
+            DirectionOfTravel::Forward,
+        ],
+        prefer_unexplored: false,
+    };
+    assert_eq!(
+        decide(&maze, Direction::North, &tie_break),
+        Some(Direction::South)
+    );
+    assert_eq!(
+        decide(&maze, Direction::East, &tie_break),
+        Some(Direction::South)
+    );
+    // The order only breaks ties: going back is not closer to the goal.
+    assert_eq!(
+        decide(&maze, Direction::South, &tie_break),
+        Some(Direction::East)
+    );
+}
+
+#[test]
+fn ties_can_prefer_unexplored_cells() {
+    let mut maze = open_maze();
+    maze.set_wall(2, 1, Direction::South, Wall::Unexplored);
+    let mut tie_break = TieBreak::default();
+    assert_eq!(
+        decide(&maze, Direction::East, &tie_break),
+        Some(Direction::East)
+    );
+    tie_break.prefer_unexplored = true;
+    assert_eq!(
+        decide(&maze, Direction::East, &tie_break),
+        Some(Direction::South)
+    );
+}
+
+fn assert_same_step_maps<const W: usize, const H: usize>(
+    maze: &Maze<W, H>,
+    goal: &Goal,
+    name: &str,
+) {
+    for (mode, mode_name) in [
+        (StepMapMode::UnexploredAsPresent, "UnexploredAsPresent"),
+        (StepMapMode::UnexploredAsAbsent, "UnexploredAsAbsent"),
+    ] {
+        let mut bfs: StepMap<W, H> = StepMap::new();
+        let mut relaxed: StepMap<W, H> = StepMap::new();
+        bfs.calc_step_map(maze, mode, goal);
+        relaxed.calc_step_map_by_relaxation(maze, mode, goal);
+        assert!(
+            bfs.grid == relaxed.grid,
+            "{}: step maps differ in {}",
+            name,
+            mode_name
+        );
+    }
+}
+
+// The breadth-first search and the relaxation give the same step maps, whether the walls are
+// known or not.
+#[test]
+fn step_map_matches_relaxation() {
+    let goal = Goal::cell(Position::new(3, 3));
+    assert_same_step_maps(&Maze::<4, 4>::new(), &goal, "unexplored");
+    assert_same_step_maps(&open_maze(), &goal, "open");
+
+    // The goal walled off from the start
+    let mut maze = open_maze();
+    maze.set_wall(2, 3, Direction::South, Wall::Present);
+    maze.set_wall(3, 2, Direction::East, Wall::Present);
+    assert_same_step_maps(&maze, &goal, "walled off");
+
+    for path in common::assets() {
+        let (mut maze, config) = common::read(&path);
+        let name = path.display().to_string();
+        assert_same_step_maps(&maze, &config.goal, &name);
+
+        // Forget every third wall inside the maze
+        for row in 0..16 {
+            for col in 0..16 {
+                if (row * 16 + col) % 3 != 0 {
+                    continue;
+                }
+                if col < 15 {
+                    maze.set_wall(row, col, Direction::East, Wall::Unexplored);
+                }
+                if row < 15 {
+                    maze.set_wall(row, col, Direction::South, Wall::Unexplored);
+                }
+            }
+        }
+        assert_same_step_maps(&maze, &config.goal, &name);
+    }
+}
+
+const MODES: [StepMapMode; 2] = [
+    StepMapMode::UnexploredAsAbsent,
+    StepMapMode::UnexploredAsPresent,
+];
+
+// Follows a step map kept up to date by update_wall, and checks the step maps in both modes
+// against full calculations after every wall sensed.
+struct Incremental {
+    // The goal and the step maps for it, calculated on the first move as Adachi does
+    stepmaps: Option<(Goal, [StepMap; 2])>,
+    walls_checked: usize,
+}
+
+impl SearchStrategy for Incremental {
+    fn next_direction(
+        &mut self,
+        maze: &Maze,
+        position: Position,
+        heading: Direction,
+        goal: &Goal,
+    ) -> Option<Direction> {
+        let (_, stepmaps) = self.stepmaps.get_or_insert_with(|| {
+            let stepmaps = MODES.map(|mode| {
+                let mut stepmap: StepMap = StepMap::new();
+                stepmap.calc_step_map(maze, mode, goal);
+                stepmap
+            });
+            (*goal, stepmaps)
+        });
+        let Position { row, col } = position;
+        if *stepmaps[0].get(row, col) == 0 {
+            return None;
+        }
+        follow_step_map(maze, row, col, heading, &TieBreak::default(), &stepmaps[0])
+    }
+
+    fn wall_discovered(&mut self, maze: &Maze, row: usize, col: usize, direction: Direction) {
+        let Some((goal, stepmaps)) = self.stepmaps.as_mut() else {
+            return;
+        };
+        for (mode, stepmap) in MODES.iter().zip(stepmaps.iter_mut()) {
+            stepmap.update_wall(maze, *mode, row, col, direction);
+            let mut expected: StepMap = StepMap::new();
+            expected.calc_step_map(maze, *mode, goal);
+            assert!(
+                stepmap.grid == expected.grid,
+                "step maps differ after the {:?} wall of ({}, {})",
+                direction,
+                row,
+                col
+            );
+        }
+        self.walls_checked += 1;
+    }
+}
+
+// Searches every maze with the incrementally updated step maps.
+#[test]
+fn incremental_step_map_matches_full_calculation() {
+    for path in common::assets() {
+        let (actual, config) = common::read(&path);
+        let strategy = Incremental {
+            stepmaps: None,
+            walls_checked: 0,
+        };
+        let mut simulator = Simulator::with_strategy(actual, config, strategy);
+        let step = loop {
+            match simulator.step() {
+                Step::Moved(_) => {}
+                step => break step,
+            }
+        };
+        assert_eq!(step, Step::AtGoal, "{}", path.display());
+        assert!(simulator.strategy().walls_checked > 0, "{}", path.display());
+    }
+}
@@ -0,0 +1,201 @@
+mod common;
+
+use std::fmt::Write;
+
+use mm_maze_solver::maze::text::{detect, parse, write, Layout, ParseError};
+use mm_maze_solver::maze::{Direction, Goal, Maze, MazeConfig, Position, Start, Wall};
+
+// Parses every maze in bin/assets, writes it back and parses the output again.
+#[test]
+fn text_format_round_trips() {
+    for path in common::assets() {
+        let (maze, config) = common::read(&path);
+
+        let mut written = String::new();
+        write(&mut written, &maze, &config).unwrap();
+        let (parsed, parsed_config): (Maze, MazeConfig) =
+            parse(&written).unwrap_or_else(|e| panic!("{}: {}\n{}", path.display(), e, written));
+        assert!(parsed.grid == maze.grid, "{}: walls differ", path.display());
+        assert_eq!(parsed_config, config, "{}", path.display());
+
+        let mut rewritten = String::new();
+        write(&mut rewritten, &parsed, &parsed_config).unwrap();
+        assert_eq!(rewritten, written, "{}", path.display());
+    }
+}
+
+// The output of lines_iter has cells three characters wide.
+#[test]
+fn lines_iter_output_parses() {
+    for path in common::assets() {
+        let (maze, config) = common::read(&path);
+
+        let mut dump = String::new();
+        for line in maze.lines_iter(&config) {
+            writeln!(dump, "{}", line).unwrap();
+        }
+        let (parsed, parsed_config): (Maze, MazeConfig) =
+            parse(&dump).unwrap_or_else(|e| panic!("{}: {}\n{}", path.display(), e, dump));
+        assert!(parsed.grid == maze.grid, "{}: walls differ", path.display());
+        assert_eq!(parsed_config, config, "{}", path.display());
+    }
+}
+
+#[test]
+fn half_size_maze_round_trips() {
+    let mut maze: Maze<32, 32> = Maze::new();
+    for i in 0..31 {
+        maze.set_wall(i, i, Direction::East, Wall::Present);
+        maze.set_wall(i, 31 - i, Direction::South, Wall::Unexplored);
+    }
+    let config = MazeConfig::classic::<32, 32>();
+
+    let mut written = String::new();
+    write(&mut written, &maze, &config).unwrap();
+    assert_eq!(
+        detect(&written),
+        Ok(Layout {
+            width: 32,
+            height: 32,
+            cell_width: 1
+        })
+    );
+    let (parsed, parsed_config): (Maze<32, 32>, MazeConfig) = parse(&written).unwrap();
+    assert!(parsed.grid == maze.grid);
+    assert_eq!(parsed_config, config);
+
+    let result: Result<(Maze, MazeConfig), _> = parse(&written);
+    assert_eq!(
+        result.err(),
+        Some(ParseError::SizeMismatch {
+            width: 32,
+            height: 32
+        })
+    );
+}
+
+#[test]
+fn goal_region_and_start_are_read() {
+    let text = "\
++-+-+-+
+|   | |
++ + + +
+| |G G|
++ +-+ +
+|S|G G|
++-+-+-+
+";
+    let (maze, config): (Maze<3, 3>, MazeConfig) = parse(text).unwrap();
+    assert_eq!(
+        config.goal.cells(),
+        &[
+            Position::new(1, 1),
+            Position::new(1, 2),
+            Position::new(2, 1),
+            Position::new(2, 2)
+        ]
+    );
+    assert_eq!(
+        config.start,
+        Start {
+            position: Position::new(2, 0),
+            heading: Direction::North
+        }
+    );
+    // Walls are taken from the text, not from the default start
+    assert_eq!(maze.get(0, 0).get(Direction::South), Wall::Absent);
+
+    // A start open on several sides heads east if it can
+    let open = text.replace("+ +-+ +\n|S|", "+ +-+ +\n|S ");
+    let (_, config): (Maze<3, 3>, MazeConfig) = parse(&open).unwrap();
+    assert_eq!(config.start.heading, Direction::East);
+
+    let closed = text.replace("+ +-+ +\n|S|", "+-+-+ +\n|S|");
+    let result: Result<(Maze<3, 3>, MazeConfig), _> = parse(&closed);
+    assert_eq!(
+        result.err(),
+        Some(ParseError::StartWalledIn { line: 6, column: 2 })
+    );
+}
+
+// The default start round trips however many of its sides are open.
+#[test]
+fn default_start_round_trips() {
+    let config = MazeConfig::new(Start::default(), Goal::cell(Position::new(3, 3)));
+    for wall in [Wall::Absent, Wall::Unexplored, Wall::Present] {
+        for direction in [Direction::East, Direction::South] {
+            let mut maze: Maze<4, 4> = Maze::new();
+            maze.set_wall(0, 0, direction, wall);
+
+            let mut written = String::new();
+            write(&mut written, &maze, &config).unwrap();
+            let (parsed, parsed_config): (Maze<4, 4>, MazeConfig) = parse(&written)
+                .unwrap_or_else(|e| panic!("{:?} {:?}: {}\n{}", direction, wall, e, written));
+            assert!(parsed.grid == maze.grid);
+            assert_eq!(parsed_config, config);
+
+            let dump: String = maze
+                .lines_iter(&config)
+                .map(|l| format!("{}\n", l))
+                .collect();
+            let (parsed, parsed_config): (Maze<4, 4>, MazeConfig) = parse(&dump).unwrap();
+            assert!(parsed.grid == maze.grid);
+            assert_eq!(parsed_config, config);
+        }
+    }
+}
+
+// A maze whose start is in the south-west corner heading north, as in the .maz archives
+#[test]
+fn start_in_another_corner() {
+    let start = Start {
+        position: Position::new(2, 0),
+        heading: Direction::North,
+    };
+    let maze: Maze<3, 3> = Maze::with_start(&start);
+    assert_eq!(maze.get(2, 0).get(Direction::East), Wall::Present);
+    assert_eq!(maze.get(2, 0).get(Direction::North), Wall::Absent);
+    assert_eq!(maze.get(0, 0).get(Direction::South), Wall::Absent);
+
+    let config = MazeConfig::new(start, Goal::cell(Position::new(1, 1)));
+    let mut written = String::new();
+    write(&mut written, &maze, &config).unwrap();
+    let (parsed, parsed_config): (Maze<3, 3>, MazeConfig) = parse(&written).unwrap();
+    assert!(parsed.grid == maze.grid);
+    assert_eq!(parsed_config, config);
+
+    let dump: Vec<String> = maze.lines_iter(&config).map(|l| l.to_string()).collect();
+    assert_eq!(dump[5], "| S |       |");
+}
+
+#[test]
+fn trailing_whitespace_is_ignored() {
+    let text = "\
++-+-+
+|G  |
++ +-+
+|S  |
++-+-+
+";
+    let padded: String = text.lines().map(|l| format!("{} \t \r\n", l)).collect();
+    assert_eq!(
+        detect(&padded),
+        Ok(Layout {
+            width: 2,
+            height: 2,
+            cell_width: 1
+        })
+    );
+    let (maze, config): (Maze<2, 2>, MazeConfig) = parse(text).unwrap();
+    let (parsed, parsed_config): (Maze<2, 2>, MazeConfig) = parse(&padded).unwrap();
+    assert!(parsed.grid == maze.grid);
+    assert_eq!(parsed_config, config);
+
+    // Anything else after the last wall is still an error
+    let extra = text.replace("|S  |", "|S  | x");
+    let result: Result<(Maze<2, 2>, MazeConfig), _> = parse(&extra);
+    assert_eq!(
+        result.err(),
+        Some(ParseError::TooManyColumns { line: 4, column: 6 })
+    );
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use mm_maze_solver::maze::validate::Diagnostic;
+use mm_maze_solver::maze::{Direction, Goal, Maze, MazeConfig, Position, Start, Wall};
+
+#[test]
+fn assets_have_no_errors() {
+    for path in common::assets() {
+        let (maze, config) = common::read(&path);
+        let errors: Vec<_> = maze
+            .validate(&config)
+            .into_iter()
+            .filter(Diagnostic::is_error)
+            .collect();
+        assert!(errors.is_empty(), "{}: {:?}", path.display(), errors);
+    }
+}
+
+#[test]
+fn broken_maze_is_reported() {
+    let mut maze: Maze<4, 4> = Maze::new();
+    // One side of a wall only
+    maze.grid[1][1].east = Wall::Present;
+    maze.grid[3][3].south = Wall::Absent;
+    // The goal is walled off
+    maze.set_wall(2, 3, Direction::West, Wall::Present);
+    maze.set_wall(2, 3, Direction::North, Wall::Present);
+    maze.set_wall(2, 3, Direction::South, Wall::Present);
+
+    let config = MazeConfig::new(
+        Start {
+            position: Position::new(0, 0),
+            heading: Direction::South,
+        },
+        Goal::from_cells(&[Position::new(2, 3), Position::new(0, 0)]).unwrap(),
+    );
+    assert_eq!(
+        maze.validate(&config),
+        vec![
+            Diagnostic::InconsistentWall {
+                position: Position::new(1, 1),
+                direction: Direction::East
+            },
+            Diagnostic::MissingOuterWall {
+                position: Position::new(3, 3),
+                direction: Direction::South
+            },
+            Diagnostic::StartNotWalled {
+                direction: Direction::East
+            },
+            Diagnostic::StartBlocked,
+            Diagnostic::GoalAtStart,
+            Diagnostic::UnreachableGoal {
+                position: Position::new(2, 3)
+            },
+            Diagnostic::UnreachableCell {
+                position: Position::new(2, 3)
+            },
+        ]
+    );
+}